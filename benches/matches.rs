@@ -0,0 +1,105 @@
+//! Compares the throughput of `matches!`, `unwrap_match!` and `option_match!` with hand-written matches.
+//!
+//! Run with `cargo bench`. Each macro is benchmarked in the same group as its hand-written baseline,
+//! so that criterion reports them side by side.
+
+// The baselines are hand-written on purpose.
+#![allow(clippy::match_like_matches_macro)]
+
+#[macro_use]
+extern crate matches2;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use std::hint::black_box;
+
+const LEN: usize = 100_000;
+
+#[derive(Debug)]
+enum Event {
+    Key(u32),
+    Click { x: u32, y: u32 },
+    Idle,
+}
+
+fn events() -> Vec<Event> {
+    (0..LEN as u32)
+        .map(|i| match i % 3 {
+            0 => Event::Key(i),
+            1 => Event::Click { x: i, y: i / 2 },
+            _ => Event::Idle,
+        })
+        .collect()
+}
+
+fn count_clicks(events: &[Event]) -> usize {
+    events
+        .iter()
+        .filter(|event| match event {
+            Event::Click { x, y } if x > y => true,
+            _ => false,
+        })
+        .count()
+}
+
+fn count_clicks_matches(events: &[Event]) -> usize {
+    events.iter().filter(|event| matches!(event, Event::Click { x, y } if x > y)).count()
+}
+
+fn sum_keys(keys: &[&Event]) -> u64 {
+    keys.iter()
+        .map(|event| match event {
+            Event::Key(code) => u64::from(*code),
+            other => panic!("not a key: {:?}", other),
+        })
+        .sum()
+}
+
+fn sum_keys_unwrap_match(keys: &[&Event]) -> u64 {
+    keys.iter().map(|event| unwrap_match!(event, Event::Key(code) => u64::from(*code))).sum()
+}
+
+fn sum_codes(events: &[Event]) -> u64 {
+    events
+        .iter()
+        .filter_map(|event| match event {
+            Event::Key(code) | Event::Click { x: code, .. } => Some(u64::from(*code)),
+            Event::Idle => None,
+        })
+        .sum()
+}
+
+fn sum_codes_option_match(events: &[Event]) -> u64 {
+    events
+        .iter()
+        .filter_map(|event| option_match!(event, Event::Key(code) | Event::Click { x: code, .. } => u64::from(*code)))
+        .sum()
+}
+
+fn benchmarks(c: &mut Criterion) {
+    let events = events();
+    let keys: Vec<&Event> = events.iter().filter(|event| matches!(event, Event::Key(_))).collect();
+    assert_eq!(count_clicks_matches(&events), count_clicks(&events));
+    assert_eq!(sum_keys_unwrap_match(&keys), sum_keys(&keys));
+    assert_eq!(sum_codes_option_match(&events), sum_codes(&events));
+
+    let mut group = c.benchmark_group("matches!");
+    group.throughput(Throughput::Elements(LEN as u64));
+    group.bench_function("match", |b| b.iter(|| count_clicks(black_box(&events))));
+    group.bench_function("matches!", |b| b.iter(|| count_clicks_matches(black_box(&events))));
+    group.finish();
+
+    let mut group = c.benchmark_group("unwrap_match!");
+    group.throughput(Throughput::Elements(keys.len() as u64));
+    group.bench_function("match with panic", |b| b.iter(|| sum_keys(black_box(&keys))));
+    group.bench_function("unwrap_match!", |b| b.iter(|| sum_keys_unwrap_match(black_box(&keys))));
+    group.finish();
+
+    let mut group = c.benchmark_group("option_match!");
+    group.throughput(Throughput::Elements(LEN as u64));
+    group.bench_function("match to Option", |b| b.iter(|| sum_codes(black_box(&events))));
+    group.bench_function("option_match!", |b| b.iter(|| sum_codes_option_match(black_box(&events))));
+    group.finish();
+}
+
+criterion_group!(benches, benchmarks);
+criterion_main!(benches);
@@ -0,0 +1,25 @@
+//! Detects the compiler features that some tests depend on.
+
+use std::env;
+use std::process::Command;
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    let minor = rustc_minor_version().unwrap_or(0);
+    // Older versions of Cargo warn about the instruction instead of ignoring it.
+    if minor >= 80 {
+        println!("cargo:rustc-check-cfg=cfg(if_let_guards)");
+    }
+    // `if let` guards are stable since Rust 1.95, above the minimum supported version, so their tests are gated.
+    if minor >= 95 {
+        println!("cargo:rustc-cfg=if_let_guards");
+    }
+}
+
+/// Returns the minor version of the compiler, from `rustc --version`.
+fn rustc_minor_version() -> Option<u32> {
+    let rustc = env::var_os("RUSTC")?;
+    let output = Command::new(rustc).arg("--version").output().ok()?;
+    let version = String::from_utf8(output.stdout).ok()?;
+    version.strip_prefix("rustc 1.")?.split('.').next()?.parse().ok()
+}
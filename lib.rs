@@ -1,8 +1,533 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+#[doc(hidden)]
+pub mod __private {
+    use core::fmt;
+
+    pub use core::cell::Cell;
+    pub use core::convert::From;
+    pub use core::convert::TryFrom;
+    pub use core::default::Default;
+    pub use core::iter::IntoIterator;
+    pub use core::mem::discriminant;
+    pub use core::ops::{Deref, RangeBounds};
+    pub use core::option::Option;
+    pub use core::pin::pin;
+    pub use core::result::Result;
+    pub use core::sync::atomic::{AtomicUsize, Ordering};
+    #[cfg(feature = "anyhow")]
+    pub use anyhow;
+    #[cfg(feature = "backtrace")]
+    pub use std::backtrace::Backtrace;
+    #[cfg(feature = "defmt")]
+    pub use defmt;
+    #[cfg(feature = "either")]
+    pub use either;
+    #[cfg(feature = "futures")]
+    pub use futures;
+    #[cfg(feature = "metrics")]
+    pub use metrics;
+    #[cfg(feature = "proptest")]
+    pub use proptest;
+    #[cfg(feature = "serde_json")]
+    pub use serde_json;
+    #[cfg(feature = "std")]
+    pub use std::format;
+    #[cfg(feature = "std")]
+    pub use std::sync::mpsc;
+    #[cfg(feature = "std")]
+    pub use std::thread;
+    #[cfg(feature = "std")]
+    pub use std::thread_local;
+    #[cfg(feature = "std")]
+    pub use std::time::{Duration, Instant};
+    #[cfg(feature = "std")]
+    pub use std::vec::Vec;
+
+    /// Views a string or a slice-like value as a slice of its elements, as used by
+    /// `starts_with_match!`. Strings are viewed as their bytes.
+    pub trait Elements {
+        type Item;
+
+        fn elements(&self) -> &[Self::Item];
+    }
+
+    impl<T: ?Sized + Elements> Elements for &T {
+        type Item = T::Item;
+
+        fn elements(&self) -> &[T::Item] {
+            (**self).elements()
+        }
+    }
+
+    impl Elements for str {
+        type Item = u8;
+
+        fn elements(&self) -> &[u8] {
+            self.as_bytes()
+        }
+    }
+
+    impl<T> Elements for [T] {
+        type Item = T;
+
+        fn elements(&self) -> &[T] {
+            self
+        }
+    }
+
+    impl<T, const N: usize> Elements for [T; N] {
+        type Item = T;
+
+        fn elements(&self) -> &[T] {
+            self
+        }
+    }
+
+    #[cfg(feature = "std")]
+    impl Elements for String {
+        type Item = u8;
+
+        fn elements(&self) -> &[u8] {
+            self.as_bytes()
+        }
+    }
+
+    #[cfg(feature = "std")]
+    impl<T> Elements for Vec<T> {
+        type Item = T;
+
+        fn elements(&self) -> &[T] {
+            self
+        }
+    }
+
+    /// Borrows the contents of an `Option` or `Result` of smart pointers through `Deref`,
+    /// as used by `unwrap_match_deref!`.
+    pub trait DerefInner {
+        type Output;
+
+        fn deref_inner(self) -> Self::Output;
+    }
+
+    impl<'a, P: Deref> DerefInner for &'a Option<P> {
+        type Output = Option<&'a P::Target>;
+
+        fn deref_inner(self) -> Self::Output {
+            self.as_deref()
+        }
+    }
+
+    impl<'a, P: Deref, E> DerefInner for &'a Result<P, E> {
+        type Output = Result<&'a P::Target, &'a E>;
+
+        fn deref_inner(self) -> Self::Output {
+            self.as_ref().map(|pointer| &**pointer)
+        }
+    }
+
+    /// A step of an `assert_sequence_matches!`: whether it repeats, and the predicate for the items it matches.
+    pub type Step<'a, T> = (bool, &'a dyn Fn(&T) -> bool);
+
+    /// Runs the steps of an `assert_sequence_matches!` over the items of an iterator.
+    ///
+    /// Returns the index and the item at which no step can match,
+    /// or the number of items and `None` if the iterator ended before the last step.
+    pub fn match_sequence<I: Iterator>(iter: I, steps: &[Step<I::Item>]) -> Result<(), (usize, Option<I::Item>)> {
+        assert!(steps.len() < 64, "assert_sequence_matches! supports at most 63 steps");
+        // Bit `s` is set if the steps before `s` have matched the items so far.
+        let closure = |mut states: u64| {
+            for (step, &(repeat, _)) in steps.iter().enumerate() {
+                if repeat && states & (1 << step) != 0 {
+                    states |= 1 << (step + 1);
+                }
+            }
+            states
+        };
+        let mut states = closure(1);
+        let mut count = 0;
+        for item in iter {
+            let mut next = 0;
+            for (step, &(repeat, matches)) in steps.iter().enumerate() {
+                if states & (1 << step) != 0 && matches(&item) {
+                    next |= 1 << if repeat { step } else { step + 1 };
+                }
+            }
+            states = closure(next);
+            if states == 0 {
+                return Err((count, Some(item)));
+            }
+            count += 1;
+        }
+        if states & (1 << steps.len()) == 0 {
+            return Err((count, None));
+        }
+        Ok(())
+    }
+
+    /// Pairs the items and the patterns of an `assert_matches_unordered!` with a maximum bipartite matching,
+    /// where bit `p` of `masks[i]` is set if item `i` matches pattern `p`.
+    ///
+    /// Returns the index of the item assigned to each pattern, if any.
+    #[cfg(feature = "std")]
+    pub fn match_unordered(masks: &[u64], patterns: usize) -> Vec<Option<usize>> {
+        assert!(patterns <= 64, "assert_matches_unordered! supports at most 64 patterns");
+        // Tries to assign `item` to a pattern, moving the items assigned to other patterns along the way.
+        fn augment(item: usize, masks: &[u64], owners: &mut [Option<usize>], visited: &mut u64) -> bool {
+            for pattern in 0..owners.len() {
+                let bit = 1 << pattern;
+                if masks[item] & bit != 0 && *visited & bit == 0 {
+                    *visited |= bit;
+                    let free = match owners[pattern] {
+                        Some(owner) => augment(owner, masks, owners, visited),
+                        None => true,
+                    };
+                    if free {
+                        owners[pattern] = Some(item);
+                        return true;
+                    }
+                }
+            }
+            false
+        }
+        let mut owners = vec![None; patterns];
+        for item in 0..masks.len() {
+            augment(item, masks, &mut owners, &mut 0);
+        }
+        owners
+    }
+
+    /// Lists the patterns and the items left over by `match_unordered`.
+    #[cfg(feature = "std")]
+    pub struct Unordered<'a, T: 'a>(pub &'a [T], pub &'a [&'static str], pub &'a [Option<usize>]);
+
+    #[cfg(feature = "std")]
+    impl<'a, T: fmt::Debug> fmt::Display for Unordered<'a, T> {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            let Unordered(items, patterns, owners) = *self;
+            let mut first = true;
+            for (pattern, owner) in patterns.iter().zip(owners) {
+                if owner.is_none() {
+                    f.write_str(if first { "; missing: " } else { ", " })?;
+                    write!(f, "`{}`", pattern)?;
+                    first = false;
+                }
+            }
+            first = true;
+            for (index, item) in items.iter().enumerate() {
+                if !owners.contains(&Some(index)) {
+                    f.write_str(if first { "; unexpected: " } else { ", " })?;
+                    write!(f, "`{}`", Value(item))?;
+                    first = false;
+                }
+            }
+            Ok(())
+        }
+    }
+
+    /// Lists the labels whose patterns matched, as used by `assert_unique_match!`.
+    pub struct Matched<'a, L: 'a>(pub &'a [L], pub &'a [bool]);
+
+    impl<'a, L: fmt::Debug> fmt::Display for Matched<'a, L> {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            let mut first = true;
+            for (label, _) in self.0.iter().zip(self.1).filter(|&(_, &matched)| matched) {
+                if !first {
+                    f.write_str(", ")?;
+                }
+                write!(f, "{:?}", label)?;
+                first = false;
+            }
+            Ok(())
+        }
+    }
+
+    /// Shadows the scrutinee in the guard of a `strict_matches!`, so that using it is a type error naming this type.
+    pub struct GuardUsesScrutinee;
+
+    /// Returns the `mem::discriminant` of a value, or of the value behind a reference when called as a method on one,
+    /// as used by `assert_iter_same_variants!`.
+    pub trait VariantOf {
+        fn variant_of(&self) -> core::mem::Discriminant<Self>
+        where
+            Self: Sized;
+    }
+
+    impl<T> VariantOf for T {
+        fn variant_of(&self) -> core::mem::Discriminant<T> {
+            core::mem::discriminant(self)
+        }
+    }
+
+    /// Passes through the result of `matches!`, marked `#[must_use]` with the `must_use` feature.
+    #[cfg(feature = "must_use")]
+    #[must_use = "the result of `matches!` should be used, e.g. with `assert!`"]
+    #[inline(always)]
+    pub const fn must_use(matched: bool) -> bool {
+        matched
+    }
+
+    #[cfg(not(feature = "must_use"))]
+    #[inline(always)]
+    pub const fn must_use(matched: bool) -> bool {
+        matched
+    }
+
+    /// Passes a value through, failing to compile unless it is `Send`.
+    #[inline(always)]
+    pub fn assert_send<T: Send>(value: T) -> T {
+        value
+    }
+
+    /// Passes a child accessor through, so that its argument is inferred as the type of the root node.
+    #[inline(always)]
+    pub fn children<N, C: IntoIterator<Item = N>, F: FnMut(N) -> C>(_root: &N, children: F) -> F {
+        children
+    }
+
+    /// Runs the fallible guard of `option_match_try!`, treating a failure like a `false` guard.
+    #[inline(always)]
+    pub fn try_guard<F: FnOnce() -> Option<bool>>(guard: F) -> bool {
+        guard() == Some(true)
+    }
+
+    /// Reports a failed soft assertion on stderr with the `std` feature, or does nothing without it.
+    #[cfg(feature = "std")]
+    pub fn report(message: fmt::Arguments) {
+        eprintln!("{}", message);
+    }
+
+    #[cfg(not(feature = "std"))]
+    pub fn report(_: fmt::Arguments) {}
+
+    /// Formats a captured backtrace on its own lines after a panic message, or nothing if backtraces are disabled.
+    #[cfg(feature = "backtrace")]
+    pub struct Trace<'a>(pub &'a Backtrace);
+
+    #[cfg(feature = "backtrace")]
+    impl<'a> fmt::Display for Trace<'a> {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self.0.status() {
+                std::backtrace::BacktraceStatus::Captured => write!(f, "\nmismatch backtrace:\n{}", self.0),
+                _ => Ok(()),
+            }
+        }
+    }
+
+    /// Formats a value for a default panic message.
+    ///
+    /// With the `std` feature, setting the `MATCHES2_VERBOSE` environment variable to `1`
+    /// switches from `{:?}` to `{:#?}`.
+    pub struct Value<'a, T: ?Sized + 'a>(pub &'a T);
+
+    impl<'a, T: ?Sized + fmt::Debug> fmt::Display for Value<'a, T> {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            if verbose() {
+                write!(f, "{:#?}", self.0)
+            } else {
+                write!(f, "{:?}", self.0)
+            }
+        }
+    }
+
+    #[cfg(feature = "std")]
+    fn verbose() -> bool {
+        std::env::var_os("MATCHES2_VERBOSE").is_some_and(|value| value == "1")
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn verbose() -> bool {
+        false
+    }
+
+    /// The default panic message of `assert_matches!`: the expression, its value and the pattern.
+    ///
+    /// With the `claim_compat` feature, each of them is shown on a separate labeled line.
+    pub struct Mismatch<'a, T: ?Sized + 'a>(pub &'static str, pub &'a T, pub &'static str);
+
+    impl<'a, T: ?Sized + fmt::Debug> fmt::Display for Mismatch<'a, T> {
+        #[cfg(not(feature = "claim_compat"))]
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "assertion failed: `{}` does not match `{}`", Value(self.1), self.2)
+        }
+
+        #[cfg(feature = "claim_compat")]
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(
+                f,
+                "assertion failed: expression does not match pattern\n  expression: {}\n      actual: {}\n     pattern: {}",
+                self.0,
+                Value(self.1),
+                self.2
+            )
+        }
+    }
+
+    /// Formats a value with `{:?}`, truncated to a number of characters followed by `...`.
+    pub struct Truncated<'a, T: ?Sized + 'a>(pub &'a T, pub usize);
+
+    impl<'a, T: ?Sized + fmt::Debug> fmt::Display for Truncated<'a, T> {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            let mut limit = Limit { inner: f, remaining: self.1, truncated: false };
+            match fmt::write(&mut limit, format_args!("{:?}", self.0)) {
+                Err(_) if limit.truncated => limit.inner.write_str("..."),
+                result => result,
+            }
+        }
+    }
+
+    struct Limit<'a, 'b: 'a> {
+        inner: &'a mut fmt::Formatter<'b>,
+        remaining: usize,
+        truncated: bool,
+    }
+
+    impl<'a, 'b> fmt::Write for Limit<'a, 'b> {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            match s.char_indices().nth(self.remaining) {
+                Some((end, _)) => {
+                    self.inner.write_str(&s[..end])?;
+                    self.remaining = 0;
+                    self.truncated = true;
+                    Err(fmt::Error)
+                }
+                None => {
+                    self.remaining -= s.chars().count();
+                    self.inner.write_str(s)
+                }
+            }
+        }
+    }
+
+    /// Formats the path of a variant for `assert_variant!`, without the `r#` prefix of raw identifiers,
+    /// as in `{:?}` output.
+    pub struct VariantPath(pub &'static str);
+
+    impl fmt::Display for VariantPath {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            for (index, segment) in self.0.split("::").enumerate() {
+                if index > 0 {
+                    f.write_str("::")?;
+                }
+                f.write_str(segment.trim_start_matches("r#"))?;
+            }
+            Ok(())
+        }
+    }
+
+    /// Formats the name of the variant of an enum value for `assert_variant!`, derived from its
+    /// `{:?}` output and prefixed with the path of the expected variant, e.g. `Foo::B`.
+    pub struct Variant<'a, T: ?Sized + 'a>(pub &'static str, pub &'a T);
+
+    impl<'a, T: ?Sized + fmt::Debug> fmt::Display for Variant<'a, T> {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            if let Some(end) = self.0.rfind("::") {
+                write!(f, "{}::", VariantPath(&self.0[..end]))?;
+            }
+            let mut name = Name { inner: f, done: false };
+            match fmt::write(&mut name, format_args!("{:?}", self.1)) {
+                Err(_) if name.done => Ok(()),
+                result => result,
+            }
+        }
+    }
+
+    struct Name<'a, 'b: 'a> {
+        inner: &'a mut fmt::Formatter<'b>,
+        done: bool,
+    }
+
+    impl<'a, 'b> fmt::Write for Name<'a, 'b> {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            match s.find(|c: char| c == '(' || c == '{' || c.is_whitespace()) {
+                Some(end) => {
+                    self.inner.write_str(&s[..end])?;
+                    self.done = true;
+                    Err(fmt::Error)
+                }
+                None => self.inner.write_str(s),
+            }
+        }
+    }
+
+    /// Lists the arms of a `MatchCoverage` that were never hit.
+    pub struct Unhit<'a>(pub &'a crate::MatchCoverage);
+
+    impl<'a> fmt::Display for Unhit<'a> {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            let mut first = true;
+            for arm in (0..self.0.arms).filter(|&arm| !self.0.is_hit(arm)) {
+                if !first {
+                    f.write_str(", ")?;
+                }
+                write!(f, "{}", arm)?;
+                first = false;
+            }
+            Ok(())
+        }
+    }
+
+    /// Lists the labels of the failed checks in a `(label, passed)` list, as used by
+    /// `assert_matches_partial!`.
+    pub struct Failing<'a>(pub &'a [(&'static str, bool)]);
+
+    impl<'a> fmt::Display for Failing<'a> {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            let mut first = true;
+            for &(label, passed) in self.0 {
+                if !passed {
+                    if !first {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "`{}`", label)?;
+                    first = false;
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
 /// Check if an expression matches a refutable pattern.
 ///
-/// Syntax: `matches!(` *expression* `,` *pattern* `)`
+/// Syntax: `matches!(` [ *attributes* ] *expression* `,` *pattern* `)`
 ///
 /// Return a boolean, true if the expression matches the pattern, false otherwise.
+/// The expression is evaluated exactly once, and the guard only if the pattern itself matched.
+/// It is used directly as the scrutinee of a `match`, so temporaries it creates, e.g. in a method chain like
+/// `config.section("net").unwrap().mode()` that returns a reference into a temporary, live until the match ends.
+///
+/// Instead of a boolean guard, `matches!`, `assert_matches!`, `debug_assert_matches!`, `unwrap_match!` and
+/// `option_match!` accept a single `if let` guard, e.g. `Some(s) if let Ok(n) = s.parse::<i32>()`,
+/// whose bindings can be used in the result. `if let` guards require Rust 1.95 or newer.
+/// Chaining one with `&&` is not supported, because the `&&` would be parsed as part of the scrutinee;
+/// move the condition into the pattern instead, e.g. `Some(s) if let Ok(n @ 1..) = s.parse::<i32>()`:
+///
+/// ```compile_fail
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     assert!(matches!(Some("3"), Some(s) if let Ok(n) = s.parse::<i32>() && n > 0));
+/// }
+/// ```
+///
+/// Leading attributes, such as `#[allow(unreachable_patterns)]`, are applied to the generated match arms.
+///
+/// With the `must_use` feature, discarding the result, e.g. by writing `matches!(x, Some(_));` as a statement
+/// instead of inside an `assert!`, triggers the `unused_must_use` lint:
+///
+#[cfg_attr(feature = "must_use", doc = "```compile_fail")]
+#[cfg_attr(not(feature = "must_use"), doc = "```ignore")]
+/// #![deny(unused_must_use)]
+///
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     matches!(Some(1), Some(_));
+/// }
+/// ```
 ///
 /// # Examples
 ///
@@ -29,11 +554,29 @@
 /// ```
 #[macro_export]
 macro_rules! matches {
+    ($expression:expr, $($pattern:pat)|* if let $guardpattern:pat = $guard:expr) => {
+        $crate::__private::must_use(
+            match $expression {
+                $($pattern)|* if let $guardpattern = $guard => true,
+                _ => false
+            }
+        )
+    };
+    ($(#[$attr:meta])+ $expression:expr, $($pattern:pat)|* $(if $ifguard:expr)?) => {
+        $crate::__private::must_use(
+            match $expression {
+                $(#[$attr])+ $($pattern)|* $(if $ifguard)? => true,
+                $(#[$attr])+ _ => false
+            }
+        )
+    };
     ($expression:expr, $($pattern:pat)|* $(if $ifguard:expr)?) => {
-        match $expression {
-            $($pattern)|* $(if $ifguard)? => true,
-            _ => false
-        }
+        $crate::__private::must_use(
+            match $expression {
+                $($pattern)|* $(if $ifguard)? => true,
+                _ => false
+            }
+        )
     };
 }
 
@@ -45,6 +588,11 @@ macro_rules! matches {
 /// that contains the pattern in it.
 /// NB: The error message is passed through to panic! verbatim, so you can do `unwrap_match!(..., "{}", 2)`.
 ///
+/// The expression is evaluated once and matched in place, so *result* can either move out of it
+/// or borrow from it with `ref` bindings, as long as the expression is a place that outlives the result.
+///
+/// The pattern may cover every case, e.g. `Foo::A(x) | Foo::B(x)`, without an unreachable pattern warning.
+///
 /// # Examples
 ///
 /// ```
@@ -65,15 +613,29 @@ macro_rules! matches {
 /// ```
 #[macro_export]
 macro_rules! unwrap_match {
+    ($expression:expr, $($pattern:pat)|* if let $guardpattern:pat = $guard:expr => $result:expr) => {
+        match $expression {
+            $($pattern)|* if let $guardpattern = $guard => $result,
+            ref value => panic!("assertion failed: `{}` does not match `{}`", $crate::__private::Value(value), stringify!($($pattern)|* if let $guardpattern = $guard))
+        }
+    };
+    ($expression:expr, $($pattern:pat)|* if let $guardpattern:pat = $guard:expr => $result:expr, $($msg:tt)+) => {
+        match $expression {
+            $($pattern)|* if let $guardpattern = $guard => $result,
+            _ => panic!($($msg)+)
+        }
+    };
     ($expression:expr, $($pattern:pat)|* $(if $ifguard:expr)? => $result:expr) => {
         match $expression {
             $($pattern)|* $(if $ifguard)? => $result,
-            _ => panic!("assertion failed: `{:?}` does not match `{}`", $expression, stringify!($($pattern)|* $(if $ifguard)?))
+            #[allow(unreachable_patterns)]
+            ref value => panic!("assertion failed: `{}` does not match `{}`", $crate::__private::Value(value), stringify!($($pattern)|* $(if $ifguard)?))
         }
     };
     ($expression:expr, $($pattern:pat)|* $(if $ifguard:expr)? => $result:expr, $($msg:tt)+) => {
         match $expression {
             $($pattern)|* $(if $ifguard)? => $result,
+            #[allow(unreachable_patterns)]
             _ => panic!($($msg)+)
         }
     };
@@ -85,6 +647,21 @@ macro_rules! unwrap_match {
 /// 
 /// Syntax: `option_match!(` *expression* `,` *pattern* `=>` *result* `)`
 ///
+/// The result may borrow from what the bindings refer to, such as a `&str` binding or a `ref` binding into a
+/// place, but not from a value moved into a binding or from a temporary created by the guard: both are dropped
+/// at the end of the match arm, so the guard's work has to be repeated in the result, and a result computed
+/// from an owned binding has to own its data, e.g. with `to_string()`:
+///
+/// ```compile_fail
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     let name = String::from(" x ");
+///     let trimmed: Option<&str> = option_match!(name, x if !x.trim().is_empty() => x.trim());
+/// }
+/// ```
+///
 /// # Examples
 /// ```
 /// #[macro_use]
@@ -101,13 +678,23 @@ macro_rules! unwrap_match {
 ///         .filter_map(|foo| option_match!(foo, &Foo::A(i) if i <= 3 => i))
 ///         .collect::<Vec<i32>>();
 ///     assert_eq!(&vec[..], &[1, 3]);
+///
+///     let name = String::from(" x ");
+///     assert_eq!(option_match!(name, ref x if !x.trim().is_empty() => x.trim()), Some("x"));
 /// }
 /// ```
 #[macro_export]
 macro_rules! option_match {
+    ($expression:expr, $($pattern:pat)|* if let $guardpattern:pat = $guard:expr => $result:expr) => {
+        match $expression {
+            $($pattern)|* if let $guardpattern = $guard => Some($result),
+            _ => None
+        }
+    };
     ($expression:expr, $($pattern:pat)|* $(if $ifguard:expr)? => $result:expr) => {
         match $expression {
             $($pattern)|* $(if $ifguard)? => Some($result),
+            #[allow(unreachable_patterns)]
             _ => None
         }
     };
@@ -115,12 +702,29 @@ macro_rules! option_match {
 
 /// Assert that an expression matches a refutable pattern.
 ///
-/// Syntax: `assert_matches!(` *expression* `,` *pattern* [ `,` *error message* ] `)`
+/// Syntax: `assert_matches!(` [ *attributes* ] *expression* `,` *pattern* [ `,` *error message* ] `)`
+///
+/// Syntax: `assert_matches!(` *expression* `,` *pattern* `, @fmt` *formatter* `)`
+///
+/// Syntax: `assert_matches!(` *expression* `,` *pattern* `, capture: [` *variable* `,` ... `])`
 ///
 /// If the pattern does not match, this macro panics with the given error message or a default one
 /// that contains the pattern in it.
 /// NB: The error message is passed through to panic! verbatim, so you can do `assert_matches!(..., "{}", 2)`.
 ///
+/// With `@fmt`, the value is shown in the default message as *formatter*`(&value)` instead of with `Debug`.
+/// The formatter is only called if the pattern does not match.
+///
+/// With `capture:`, the default message is followed by the `Debug` values of the listed variables,
+/// e.g. `, req_id = 7, attempt = 2`. They are only formatted if the pattern does not match.
+///
+/// Leading attributes, such as `#[allow(unreachable_patterns)]`, are applied to the generated match arms.
+///
+/// With the `std` feature (enabled by default), setting the `MATCHES2_VERBOSE` environment variable to `1`
+/// pretty-prints the value in the default error message with `{:#?}`.
+/// With the `claim_compat` feature, the default error message shows the expression, its value and the pattern
+/// on separate labeled lines, like the `claim` and `assert2` crates do.
+///
 /// # Examples
 ///
 /// ```
@@ -134,10 +738,50 @@ macro_rules! option_match {
 /// ```
 #[macro_export]
 macro_rules! assert_matches {
+    ($expression:expr, $($pattern:pat)|* if let $guardpattern:pat = $guard:expr) => {
+        match $expression {
+            $($pattern)|* if let $guardpattern = $guard => (),
+            ref value => panic!("{}", $crate::__private::Mismatch(stringify!($expression), value, stringify!($($pattern)|* if let $guardpattern = $guard)))
+        }
+    };
+    ($expression:expr, $($pattern:pat)|* if let $guardpattern:pat = $guard:expr, $($msg:tt)+) => {
+        match $expression {
+            $($pattern)|* if let $guardpattern = $guard => (),
+            _ => panic!($($msg)+)
+        }
+    };
+    ($(#[$attr:meta])+ $expression:expr, $($pattern:pat)|* $(if $ifguard:expr)?) => {
+        match $expression {
+            $(#[$attr])+ $($pattern)|* $(if $ifguard)? => (),
+            $(#[$attr])+ ref value => panic!("{}", $crate::__private::Mismatch(stringify!($expression), value, stringify!($($pattern)|* $(if $ifguard)?)))
+        }
+    };
+    ($(#[$attr:meta])+ $expression:expr, $($pattern:pat)|* $(if $ifguard:expr)?, $($msg:tt)+) => {
+        match $expression {
+            $(#[$attr])+ $($pattern)|* $(if $ifguard)? => (),
+            $(#[$attr])+ _ => panic!($($msg)+)
+        }
+    };
     ($expression:expr, $($pattern:pat)|* $(if $ifguard:expr)?) => {
         match $expression {
             $($pattern)|* $(if $ifguard)? => (),
-            _ => panic!("assertion failed: `{:?}` does not match `{}`", $expression, stringify!($($pattern)|* $(if $ifguard)?))
+            ref value => panic!("{}", $crate::__private::Mismatch(stringify!($expression), value, stringify!($($pattern)|* $(if $ifguard)?)))
+        }
+    };
+    ($expression:expr, $($pattern:pat)|* $(if $ifguard:expr)?, @fmt $func:expr) => {
+        match $expression {
+            $($pattern)|* $(if $ifguard)? => (),
+            ref value => panic!("assertion failed: `{}` does not match `{}`", ($func)(value), stringify!($($pattern)|* $(if $ifguard)?))
+        }
+    };
+    ($expression:expr, $($pattern:pat)|* $(if $ifguard:expr)?, capture: [$($capture:ident),+ $(,)?]) => {
+        match $expression {
+            $($pattern)|* $(if $ifguard)? => (),
+            ref value => panic!(
+                concat!("{}", $(", ", stringify!($capture), " = {}"),+),
+                $crate::__private::Mismatch(stringify!($expression), value, stringify!($($pattern)|* $(if $ifguard)?)),
+                $($crate::__private::Value(&$capture)),+
+            )
         }
     };
     ($expression:expr, $($pattern:pat)|* $(if $ifguard:expr)?, $($msg:tt)+) => {
@@ -170,10 +814,22 @@ macro_rules! assert_matches {
 #[cfg(debug_assertions)]
 #[macro_export]
 macro_rules! debug_assert_matches {
+    ($expression:expr, $($pattern:pat)|* if let $guardpattern:pat = $guard:expr) => {
+        match $expression {
+            $($pattern)|* if let $guardpattern = $guard => (),
+            ref value => panic!("{}", $crate::__private::Mismatch(stringify!($expression), value, stringify!($($pattern)|* if let $guardpattern = $guard)))
+        }
+    };
+    ($expression:expr, $($pattern:pat)|* if let $guardpattern:pat = $guard:expr, $($msg:tt)+) => {
+        match $expression {
+            $($pattern)|* if let $guardpattern = $guard => (),
+            _ => panic!($($msg)+)
+        }
+    };
     ($expression:expr, $($pattern:pat)|* $(if $ifguard:expr)?) => {
         match $expression {
             $($pattern)|* $(if $ifguard)? => (),
-            _ => panic!("assertion failed: `{:?}` does not match `{}`", $expression, stringify!($($pattern)|* $(if $ifguard)?))
+            ref value => panic!("{}", $crate::__private::Mismatch(stringify!($expression), value, stringify!($($pattern)|* $(if $ifguard)?)))
         }
     };
     ($expression:expr, $($pattern:pat)|* $(if $ifguard:expr)?, $($msg:tt)+) => {
@@ -186,64 +842,8695 @@ macro_rules! debug_assert_matches {
 #[cfg(not(debug_assertions))]
 #[macro_export]
 macro_rules! debug_assert_matches {
+    ($expression:expr, $($pattern:pat)|* if let $guardpattern:pat = $guard:expr $(, $($msg:tt)+)?) => {};
     ($expression:expr, $($pattern:pat)|* $(if $ifguard:expr)? $(, $($msg:tt)+)?) => {};
 }
 
-#[cfg(test)]
-mod tests {
-    #[test]
-    fn matches_works() {
-        let foo = Some("-12");
-        assert!(matches!(foo, Some(bar) if
-            matches!(bar.as_bytes()[0], b'+' | b'-') &&
-            matches!(bar.as_bytes()[1], b'0'..=b'9')
-        ));
-    }
-
-    #[test]
-    fn assert_matches_works() {
-        let foo = Some("-12");
-        assert_matches!(foo, Some(bar) if
-            matches!(bar.as_bytes()[0], b'+' | b'-') &&
-            matches!(bar.as_bytes()[1], b'0'..=b'9')
-        );
-    }
-
-    #[test]
-    #[should_panic(expected = "assertion failed: `Some(\"-AB\")` does not match ")]
-    fn assert_matches_panics() {
-        let foo = Some("-AB");
-        assert_matches!(foo, Some(bar) if
-            matches!(bar.as_bytes()[0], b'+' | b'-') &&
-            matches!(bar.as_bytes()[1], b'0'..=b'9')
-        );
-    }
+/// Assert that an expression matches a refutable pattern, only if a configuration predicate is true.
+///
+/// Syntax: `cfg_assert_matches!(cfg(` *predicate* `),` *expression* `,` *pattern* [ `if` *guard* ] [ `,` *error message* ] `)`
+///
+/// The predicate is any `#[cfg]` predicate, evaluated in the calling crate, e.g. `feature = "validate"`.
+/// If it is true, this is `assert_matches!`. Otherwise, the assertion is removed like any `#[cfg]` item, so the
+/// expression is neither evaluated nor type-checked, which allows gating expensive checks behind a feature
+/// of the calling crate.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     let data = [1, 2, 3];
+///     cfg_assert_matches!(cfg(debug_assertions), data.get(1), Some(_));
+///     cfg_assert_matches!(cfg(any()), data.get(5), Some(_), "never checked");
+/// }
+/// ```
+#[macro_export]
+macro_rules! cfg_assert_matches {
+    (cfg($($predicate:tt)*), $($assertion:tt)+) => {{
+        #[cfg($($predicate)*)]
+        $crate::assert_matches!($($assertion)+);
+    }};
+}
 
-    #[test]
-    fn unwrap_match_works() {
-        #[allow(dead_code)]
-        #[derive(Debug)]
-        enum Foo {
-            A(u32),
+/// Generate `is_*` predicate methods for the variants of an enum.
+///
+/// Syntax: `is_variant!(` [ `impl<` *parameters* `>` ] *enum* `, {` *method* `=>` *variant* [ *fields* ] `,` ... `})`
+///
+/// Each method is generated as `pub fn` *method* `(&self) -> bool`, implemented with
+/// `matches!(*self, Self::`*variant* [ *fields* ] `)`.
+/// The fields are written as they would be in a pattern, e.g. `(_)` or `{ .. }`.
+/// *enum* is a path to the type, with its generic arguments if it has any, in which case the parameters are
+/// declared with `impl<...>` as in an `impl` block, e.g. `is_variant!(impl<T> Tree<T>, { ... })`.
+///
+/// The method names are written out, because `macro_rules!` cannot build an identifier such as `is_active`
+/// from the variant name `Active`; that would need a procedural macro.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// pub enum Status {
+///     Active,
+///     Paused,
+///     Stopped(i32),
+/// }
+///
+/// is_variant!(Status, {
+///     is_active => Active,
+///     is_paused => Paused,
+///     is_stopped => Stopped(_),
+/// });
+///
+/// fn main() {
+///     assert!(Status::Paused.is_paused());
+///     assert!(!Status::Stopped(1).is_active());
+/// }
+/// ```
+#[macro_export]
+macro_rules! is_variant {
+    (impl<$($lifetime:lifetime,)* $($parameter:ident),* $(,)?> $enum:path, {
+        $($method:ident => $variant:ident $(($($tuple:tt)*))? $({$($struct:tt)*})?),* $(,)?
+    }) => {
+        impl<$($lifetime,)* $($parameter),*> $enum {
+            $(
+                pub fn $method(&self) -> bool {
+                    $crate::matches!(*self, Self::$variant $(($($tuple)*))? $({$($struct)*})?)
+                }
+            )*
+        }
+    };
+    ($enum:path, { $($arms:tt)* }) => {
+        $crate::is_variant!(impl<> $enum, { $($arms)* });
+    };
+}
+
+/// Assert that an expression matches a struct or tuple pattern, reporting which constraints failed.
+///
+/// Syntax: `assert_matches_partial!(` *expression* `,` *path* `{` *field* `:` *pattern* `,` ... [ `..` ] `})`
+///
+/// Syntax: `assert_matches_partial!(` *expression* `,` [ *path* ] `(` *pattern* `,` ... `))`
+///
+/// Each field (or position, for tuples) is one constraint. If the whole pattern does not match,
+/// every constraint is checked on its own and the panic message reports how many of them matched,
+/// e.g. ``matched 3/4 specified constraints; failing: `status` ``.
+/// Tuple patterns support up to 12 positions.
+/// The path of a tuple pattern cannot have generic arguments; use the indexed struct syntax instead,
+/// e.g. `Option::<u8>::Some { 0: 1..=9 }`.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// #[derive(Debug)]
+/// struct Response {
+///     status: u16,
+///     retries: u8,
+///     body: &'static str,
+/// }
+///
+/// fn main() {
+///     let response = Response { status: 200, retries: 0, body: "Hello" };
+///     assert_matches_partial!(response, Response { status: 200..=299, retries: 0, .. });
+///     assert_matches_partial!((1, "two", 3.0), (1, "two", _));
+/// }
+/// ```
+#[macro_export]
+macro_rules! assert_matches_partial {
+    ($expression:expr, ( $($pattern:pat),+ $(,)? )) => {
+        match $expression {
+            ref value => {
+                if !$crate::matches!(*value, ( $($pattern,)+ )) {
+                    let checks = $crate::assert_matches_partial!(
+                        @tuple value, [0 1 2 3 4 5 6 7 8 9 10 11], [], $($pattern),+
+                    );
+                    panic!(
+                        "assertion failed: `{}` does not match `{}`: matched {}/{} specified constraints; failing: {}",
+                        $crate::__private::Value(value),
+                        stringify!(( $($pattern),+ )),
+                        checks.iter().filter(|check| check.1).count(),
+                        checks.len(),
+                        $crate::__private::Failing(&checks)
+                    );
+                }
+            }
+        }
+    };
+    ($expression:expr, $($path:ident)::+ ( $($pattern:pat),+ $(,)? )) => {
+        match $expression {
+            ref value => {
+                if !$crate::matches!(*value, $($path)::+ ( $($pattern),+ )) {
+                    let checks = $crate::assert_matches_partial!(
+                        @struct value, [$($path)::+], [0 1 2 3 4 5 6 7 8 9 10 11], [], $($pattern),+
+                    );
+                    panic!(
+                        "assertion failed: `{}` does not match `{}`: matched {}/{} specified constraints; failing: {}",
+                        $crate::__private::Value(value),
+                        stringify!($($path)::+ ( $($pattern),+ )),
+                        checks.iter().filter(|check| check.1).count(),
+                        checks.len(),
+                        $crate::__private::Failing(&checks)
+                    );
+                }
+            }
+        }
+    };
+    ($expression:expr, $path:path { $($field:ident : $pattern:pat),+ $(, ..)? $(,)? }) => {
+        $crate::assert_matches_partial!(@fields constraints, $expression, $path, [$($field: $pattern),+])
+    };
+    ($expression:expr, $path:path { $($field:tt : $pattern:pat),+ $(,)? }) => {
+        $crate::assert_matches_partial!(@fields constraints, $expression, $path, [$($field: $pattern),+])
+    };
+    (@fields $report:ident, $expression:expr, $path:path, [$($field:tt : $pattern:pat),+]) => {
+        match $expression {
+            ref value => {
+                if !$crate::matches!(*value, $path { $($field: $pattern,)+ .. }) {
+                    let checks = [$((
+                        stringify!($field),
+                        $crate::matches!(*value, $path { $field: $pattern, .. })
+                    )),+];
+                    $crate::assert_matches_partial!(@report $report, value, checks, stringify!($path { $($field: $pattern,)+ .. }))
+                }
+            }
+        }
+    };
+    (@report constraints, $value:ident, $checks:ident, $pattern:expr) => {
+        panic!(
+            "assertion failed: `{}` does not match `{}`: matched {}/{} specified constraints; failing: {}",
+            $crate::__private::Value($value),
+            $pattern,
+            $checks.iter().filter(|check| check.1).count(),
+            $checks.len(),
+            $crate::__private::Failing(&$checks)
+        )
+    };
+    (@report fields, $value:ident, $checks:ident, $pattern:expr) => {
+        panic!(
+            "assertion failed: `{}` does not match `{}`: mismatched fields: {}",
+            $crate::__private::Value($value),
+            $pattern,
+            $crate::__private::Failing(&$checks)
+        )
+    };
+    (@struct $value:ident, [$($path:ident)::+], [$index:tt $($indices:tt)*], [$($checks:expr),*], $pattern:pat $(, $rest:pat)*) => {
+        $crate::assert_matches_partial!(
+            @struct $value, [$($path)::+], [$($indices)*],
+            [$($checks,)* (stringify!($index), $crate::matches!(*$value, $($path)::+ { $index: $pattern, .. }))],
+            $($rest),*
+        )
+    };
+    (@struct $value:ident, [$($path:ident)::+], [$($indices:tt)*], [$($checks:expr),*],) => {
+        [$($checks),*]
+    };
+    (@tuple $value:ident, [$index:tt $($indices:tt)*], [$($checks:expr),*], $pattern:pat $(, $rest:pat)*) => {
+        $crate::assert_matches_partial!(
+            @tuple $value, [$($indices)*],
+            [$($checks,)* (stringify!($index), $crate::matches!($value.$index, $pattern))],
+            $($rest),*
+        )
+    };
+    (@tuple $value:ident, [$($indices:tt)*], [$($checks:expr),*],) => {
+        [$($checks),*]
+    };
+}
+
+/// Generate a `const fn` that maps its argument to a value by pattern.
+///
+/// Syntax: `const_matcher!(` [ *attributes* ] [ *visibility* ] `fn` *name* `(` *argument* `:` *type* `) ->` *return type* `{`
+/// *pattern* [ `if` *guard* ] `=>` *result* `,` ... `})`
+///
+/// The arms are expanded into a `match` on the argument, so they must be exhaustive;
+/// the last arm is usually a `_ =>` default. The generated function can be called in `const` contexts,
+/// e.g. to build lookup tables. Attributes, such as doc comments or `#[inline]`, are applied to the function.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// #[derive(Clone, Copy, Debug, PartialEq)]
+/// enum Class {
+///     Digit,
+///     Alpha,
+///     Other,
+/// }
+///
+/// const_matcher!(fn classify(b: u8) -> Class {
+///     b'0'..=b'9' => Class::Digit,
+///     b'a'..=b'z' | b'A'..=b'Z' => Class::Alpha,
+///     _ => Class::Other,
+/// });
+///
+/// const TABLE: [Class; 256] = {
+///     let mut table = [Class::Other; 256];
+///     let mut i = 0;
+///     while i < 256 {
+///         table[i] = classify(i as u8);
+///         i += 1;
+///     }
+///     table
+/// };
+///
+/// fn main() {
+///     assert_eq!(TABLE[b'7' as usize], Class::Digit);
+///     assert_eq!(classify(b'q'), Class::Alpha);
+/// }
+/// ```
+#[macro_export]
+macro_rules! const_matcher {
+    ($(#[$attr:meta])* $vis:vis fn $name:ident($argument:ident : $type:ty) -> $return:ty {
+        $($($pattern:pat)|+ $(if $ifguard:expr)? => $result:expr),+ $(,)?
+    }) => {
+        $(#[$attr])*
+        $vis const fn $name($argument: $type) -> $return {
+            match $argument {
+                $($($pattern)|+ $(if $ifguard)? => $result,)+
+            }
+        }
+    };
+}
+
+/// The inverse of `option_match!`: evaluate to a result only if the expression does *not* match a pattern.
+///
+/// Syntax: `unless_match!(` *expression* `,` *pattern* `=>` *result* `, else` *fallback* `)`
+///
+/// The macro evaluates to *result* if *pattern* does not match, otherwise to *fallback*.
+/// Since *result* is only evaluated when nothing matched, it cannot use the pattern's bindings,
+/// but it may use the variable the expression refers to.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// enum Input {
+///     Malformed(&'static str),
+///     Text(&'static str),
+/// }
+///
+/// fn process(input: Input) -> usize {
+///     match input {
+///         Input::Malformed(s) | Input::Text(s) => s.len(),
+///     }
+/// }
+///
+/// fn main() {
+///     let input = Input::Text("Hello");
+///     let len = unless_match!(input, Input::Malformed(_) => process(input), else Default::default());
+///     assert_eq!(len, 5);
+/// }
+/// ```
+#[macro_export]
+macro_rules! unless_match {
+    ($expression:expr, $($pattern:pat)|* $(if $ifguard:expr)? => $result:expr, else $fallback:expr) => {
+        match $expression {
+            $($pattern)|* $(if $ifguard)? => $fallback,
+            _ => $result
+        }
+    };
+}
+
+/// Check if two expressions are the same enum variant, regardless of their payloads.
+///
+/// Syntax: `same_variant!(` *expression* `,` *expression* `)`
+///
+/// Return a boolean, true if the discriminants of both values (as returned by
+/// `std::mem::discriminant`) are equal, false otherwise.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     assert!(same_variant!(Some(1), Some(2)));
+///     assert!(!same_variant!(Some(1), None));
+/// }
+/// ```
+#[macro_export]
+macro_rules! same_variant {
+    ($a:expr, $b:expr) => {
+        $crate::__private::discriminant(&$a) == $crate::__private::discriminant(&$b)
+    };
+}
+
+/// Assert that two expressions are the same enum variant, regardless of their payloads.
+///
+/// Syntax: `assert_same_variant!(` *expression* `,` *expression* [ `,` *error message* ] `)`
+///
+/// If the variants differ, this macro panics with the given error message or a default one
+/// that contains both values.
+/// NB: The error message is passed through to panic! verbatim, so you can do `assert_same_variant!(..., "{}", 2)`.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     let expected: Result<u32, ()> = Ok(0);
+///     assert_same_variant!("42".parse::<u32>().map_err(|_| ()), expected);
+/// }
+/// ```
+#[macro_export]
+macro_rules! assert_same_variant {
+    ($a:expr, $b:expr) => {
+        match (&$a, &$b) {
+            (a, b) => if !$crate::same_variant!(*a, *b) {
+                panic!("assertion failed: `{}` is not the same variant as `{}`", $crate::__private::Value(a), $crate::__private::Value(b))
+            }
+        }
+    };
+    ($a:expr, $b:expr, $($msg:tt)+) => {
+        if !$crate::same_variant!($a, $b) {
+            panic!($($msg)+)
+        }
+    };
+}
+
+/// Check if an expression matches any of a list of patterns.
+///
+/// Syntax: `matches_any_of!(` *expression* `, [` *pattern* [ `if` *guard* ] `,` ... `]` [ `if` *guard* ] `)`
+///
+/// Unlike alternation with `|`, the patterns in the list may bind different variables.
+/// The patterns are tried in order and the first match short-circuits the rest.
+/// The trailing guard is shared and evaluated after one of the patterns matched, but it cannot use their bindings.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// enum Shape {
+///     Circle(f64),
+///     Rect(f64, f64),
+///     Empty,
+/// }
+///
+/// fn main() {
+///     let shape = Shape::Rect(2.0, 2.0);
+///     assert!(matches_any_of!(shape, [Shape::Circle(r) if r > 1.0, Shape::Rect(w, h) if w == h]));
+///     assert!(!matches_any_of!(Shape::Empty, [Shape::Circle(_), Shape::Rect(..)]));
+/// }
+/// ```
+#[macro_export]
+macro_rules! matches_any_of {
+    ($expression:expr, [$($($pattern:pat)|+ $(if $ifguard:expr)?),+ $(,)?] $(if $sharedguard:expr)?) => {
+        match $expression {
+            ref value => (false $(|| $crate::matches!(*value, $($pattern)|+ $(if $ifguard)?))+) $(&& $sharedguard)?
+        }
+    };
+}
+
+/// Returns `Result::Ok` if the pattern matches with the given value, or `Result::Err` containing the pattern otherwise.
+///
+/// Syntax: `try_unwrap_match!(` *expression* `,` *pattern* `=>` *result* `)`
+///
+/// The error is the stringified pattern as a `&'static str`, so this macro does not allocate
+/// and can be used with `?` in `no_std` code.
+///
+/// # Examples
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// enum Frame {
+///     Data(u32),
+///     Close,
+/// }
+///
+/// fn payload(frame: Frame) -> Result<u32, &'static str> {
+///     let data = try_unwrap_match!(frame, Frame::Data(n) if n > 0 => n)?;
+///     Ok(data * 2)
+/// }
+///
+/// fn main() {
+///     assert_eq!(payload(Frame::Data(2)), Ok(4));
+///     assert_eq!(payload(Frame::Close), Err("Frame::Data(n) if n > 0"));
+/// }
+/// ```
+#[macro_export]
+macro_rules! try_unwrap_match {
+    ($expression:expr, $($pattern:pat)|* $(if $ifguard:expr)? => $result:expr) => {
+        match $expression {
+            $($pattern)|* $(if $ifguard)? => $crate::__private::Result::Ok($result),
+            #[allow(unreachable_patterns)]
+            _ => $crate::__private::Result::Err(stringify!($($pattern)|* $(if $ifguard)?))
+        }
+    };
+}
+
+/// Check if the dereferenced value of an expression matches a refutable pattern.
+///
+/// Syntax: `deref_matches!(` *expression* `,` *pattern* `)`
+///
+/// The pattern is matched against `Deref::deref(&`*expression*`)`, so smart pointers such as
+/// `Cow<str>`, `Box<str>` or `String` can be matched against literals.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// use std::borrow::Cow;
+///
+/// fn main() {
+///     let cow: Cow<str> = Cow::Owned("expected".to_string());
+///     assert!(deref_matches!(cow, "expected" | "other"));
+/// }
+/// ```
+#[macro_export]
+macro_rules! deref_matches {
+    ($expression:expr, $($pattern:pat)|* $(if $ifguard:expr)?) => {
+        match $crate::__private::Deref::deref(&$expression) {
+            $($pattern)|* $(if $ifguard)? => true,
+            _ => false
+        }
+    };
+}
+
+/// Assert that a state machine transitions from one state to another.
+///
+/// Syntax: `assert_transition!(` *machine* `,` *pattern* `,` *action* `,` *pattern* `)`
+///
+/// Asserts that *machine* matches the first pattern, calls *action* with `&mut` *machine*,
+/// then asserts that *machine* matches the second pattern.
+/// The panic message says whether the state before or after the transition did not match.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// #[derive(Debug)]
+/// enum Light {
+///     Off,
+///     On(u8),
+/// }
+///
+/// impl Light {
+///     fn toggle(&mut self) {
+///         *self = match *self {
+///             Light::Off => Light::On(100),
+///             Light::On(_) => Light::Off,
+///         };
+///     }
+/// }
+///
+/// fn main() {
+///     let mut light = Light::Off;
+///     assert_transition!(light, Light::Off, Light::toggle, Light::On(100));
+///     assert_transition!(light, Light::On(_), |light: &mut Light| light.toggle(), Light::Off);
+/// }
+/// ```
+#[macro_export]
+macro_rules! assert_transition {
+    ($machine:expr, $($from:pat)|+ $(if $fromguard:expr)?, $action:expr, $($to:pat)|+ $(if $toguard:expr)?) => {{
+        match $machine {
+            $($from)|+ $(if $fromguard)? => (),
+            ref value => panic!(
+                "assertion failed: state `{}` does not match `{}` before the transition",
+                $crate::__private::Value(value),
+                stringify!($($from)|+ $(if $fromguard)?)
+            )
+        }
+        ($action)(&mut $machine);
+        match $machine {
+            $($to)|+ $(if $toguard)? => (),
+            ref value => panic!(
+                "assertion failed: state `{}` does not match `{}` after the transition",
+                $crate::__private::Value(value),
+                stringify!($($to)|+ $(if $toguard)?)
+            )
+        }
+    }};
+}
+
+/// Returns the label of the first pattern that matches the given value, or `Option::None` if none match.
+///
+/// Syntax: `labeled_match!(` *expression* `, {` *label* `=>` *pattern* [ `if` *guard* ] `,` ... `})`
+///
+/// # Examples
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// enum Event {
+///     Ping,
+///     Data(usize),
+///     Close,
+/// }
+///
+/// fn main() {
+///     let events = [Event::Ping, Event::Data(10), Event::Data(1000), Event::Close];
+///     let labels = events.iter()
+///         .map(|event| labeled_match!(*event, {
+///             "ping" => Event::Ping,
+///             "small" => Event::Data(n) if n < 100,
+///             "large" => Event::Data(_),
+///         }))
+///         .collect::<Vec<_>>();
+///     assert_eq!(labels, [Some("ping"), Some("small"), Some("large"), None]);
+/// }
+/// ```
+#[macro_export]
+macro_rules! labeled_match {
+    ($expression:expr, { $($label:expr => $($pattern:pat)|+ $(if $ifguard:expr)?),+ $(,)? }) => {
+        match $expression {
+            $($($pattern)|+ $(if $ifguard)? => $crate::__private::Option::Some($label),)+
+            #[allow(unreachable_patterns)]
+            _ => $crate::__private::Option::None
+        }
+    };
+}
+
+/// Assert that an expression matches a refutable pattern, truncating the value in the error message.
+///
+/// Syntax: `assert_matches_truncated!(` *expression* `,` *pattern* `,` *max length* `)`
+///
+/// Like `assert_matches!`, but at most *max length* characters of the value's `Debug` representation
+/// are included in the error message, followed by `...` if it was truncated.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     let data = vec![0u8; 4096];
+///     assert_matches_truncated!(data.first(), Some(0), 80);
+/// }
+/// ```
+#[macro_export]
+macro_rules! assert_matches_truncated {
+    ($expression:expr, $($pattern:pat)|* $(if $ifguard:expr)?, $max_len:expr) => {
+        match $expression {
+            $($pattern)|* $(if $ifguard)? => (),
+            ref value => panic!(
+                "assertion failed: `{}` does not match `{}`",
+                $crate::__private::Truncated(value, $max_len),
+                stringify!($($pattern)|* $(if $ifguard)?)
+            )
+        }
+    };
+}
+
+/// Assert that every pair of consecutive elements of an iterator matches a tuple pattern.
+///
+/// Syntax: `assert_matches_monotonic!(` *iterator* `,` *pattern* `)`
+///
+/// The pattern is matched against `(&previous, &next)` for each window of two consecutive elements.
+/// If a pair does not match, this macro panics with a message containing the indices of both elements.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     assert_matches_monotonic!(vec![1, 2, 2, 5], (a, b) if a <= b);
+/// }
+/// ```
+#[macro_export]
+macro_rules! assert_matches_monotonic {
+    ($iter:expr, $($pattern:pat)|* $(if $ifguard:expr)?) => {{
+        let mut iter = $crate::__private::IntoIterator::into_iter($iter);
+        if let Some(mut previous) = iter.next() {
+            for (index, next) in iter.enumerate() {
+                match (&previous, &next) {
+                    $($pattern)|* $(if $ifguard)? => (),
+                    (previous, next) => panic!(
+                        "assertion failed: elements {} and {} (`{}`, `{}`) do not match `{}`",
+                        index,
+                        index + 1,
+                        $crate::__private::Value(previous),
+                        $crate::__private::Value(next),
+                        stringify!($($pattern)|* $(if $ifguard)?)
+                    )
+                }
+                previous = next;
+            }
+        }
+    }};
+}
+
+/// Assert that an expression matches a refutable pattern, reporting how long the expression took to evaluate.
+///
+/// Syntax: `assert_matches_timed!(` *expression* `,` *pattern* `)`
+///
+/// Like `assert_matches!`, but the time taken to evaluate the expression is measured and included
+/// in the panic message, e.g. `(scrutinee evaluated in 1.2ms)`. The value of the expression is moved before it is
+/// matched, so pass a reference to check a variable that is used afterwards.
+/// This macro requires the `std` feature.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     assert_matches_timed!("42".parse::<u32>(), Ok(42));
+/// }
+/// ```
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! assert_matches_timed {
+    ($expression:expr, $($pattern:pat)|* $(if $ifguard:expr)?) => {{
+        let start = $crate::__private::Instant::now();
+        match $expression {
+            value => {
+                let elapsed = start.elapsed();
+                match value {
+                    $($pattern)|* $(if $ifguard)? => (),
+                    #[allow(unreachable_patterns)]
+                    ref value => panic!(
+                        "assertion failed: `{}` does not match `{}` (scrutinee evaluated in {:?})",
+                        $crate::__private::Value(value),
+                        stringify!($($pattern)|* $(if $ifguard)?),
+                        elapsed
+                    )
+                }
+            }
+        }
+    }};
+}
+
+/// Check if a set of flags contains the given flags.
+///
+/// Syntax: `flags_match!(` *value* `,` *flags* `)`
+///
+/// Pattern matching does not work with `bitflags`-style types, so this macro expands to *value*`.contains(`*flags*`)`.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+/// #[macro_use]
+/// extern crate bitflags;
+///
+/// bitflags! {
+///     #[derive(Debug)]
+///     struct Permissions: u8 {
+///         const READ = 0b001;
+///         const WRITE = 0b010;
+///     }
+/// }
+///
+/// fn main() {
+///     let permissions = Permissions::READ | Permissions::WRITE;
+///     assert!(flags_match!(permissions, Permissions::READ));
+/// }
+/// ```
+#[macro_export]
+macro_rules! flags_match {
+    ($value:expr, $flags:expr) => {
+        $value.contains($flags)
+    };
+}
+
+/// Assert that a set of flags contains the given flags.
+///
+/// Syntax: `assert_flags_match!(` *value* `,` *flags* [ `,` *error message* ] `)`
+///
+/// If *value* does not contain *flags*, this macro panics with the given error message or a default one
+/// that contains the missing flags, computed with *flags*`.difference(`*value*`)`, so the flags type must be `Copy` and `Debug`.
+/// NB: The error message is passed through to panic! verbatim, so you can do `assert_flags_match!(..., "{}", 2)`.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+/// #[macro_use]
+/// extern crate bitflags;
+///
+/// bitflags! {
+///     #[derive(Clone, Copy, Debug)]
+///     struct Permissions: u8 {
+///         const READ = 0b001;
+///         const WRITE = 0b010;
+///     }
+/// }
+///
+/// fn main() {
+///     let permissions = Permissions::READ | Permissions::WRITE;
+///     assert_flags_match!(permissions, Permissions::WRITE);
+/// }
+/// ```
+#[macro_export]
+macro_rules! assert_flags_match {
+    ($value:expr, $flags:expr) => {
+        match (&$value, $flags) {
+            (value, flags) => if !value.contains(flags) {
+                panic!(
+                    "assertion failed: `{}` does not contain `{}` (missing `{}`)",
+                    $crate::__private::Value(value),
+                    $crate::__private::Value(&flags),
+                    $crate::__private::Value(&flags.difference(*value))
+                )
+            }
+        }
+    };
+    ($value:expr, $flags:expr, $($msg:tt)+) => {
+        if !$crate::flags_match!($value, $flags) {
+            panic!($($msg)+)
+        }
+    };
+}
+
+/// Fold over the results of the elements of an iterator that match a pattern.
+///
+/// Syntax: `scan_matches!(` *iterator* `,` *pattern* `=>` *result* `,` *init* `, |` *accumulator* `,` *x* `|` *fold* `)`
+///
+/// For every element that matches *pattern*, *result* is bound to *x* and the accumulator is replaced
+/// with *fold*. Elements that do not match are skipped. The macro evaluates to the final accumulator.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     let data = [Ok(1), Err("skipped"), Ok(2), Ok(30)];
+///     let sum = scan_matches!(data.iter(), &Ok(n) if n < 10 => n, 0, |sum, n| sum + n);
+///     assert_eq!(sum, 3);
+/// }
+/// ```
+#[macro_export]
+macro_rules! scan_matches {
+    ($iter:expr, $($pattern:pat)|* $(if $ifguard:expr)? => $result:expr, $init:expr, |$acc:pat, $x:pat| $fold:expr) => {{
+        let mut acc = $init;
+        for item in $crate::__private::IntoIterator::into_iter($iter) {
+            acc = match $crate::option_match!(item, $($pattern)|* $(if $ifguard)? => $result) {
+                Some($x) => {
+                    let $acc = acc;
+                    $fold
+                }
+                None => acc,
+            };
+        }
+        acc
+    }};
+}
+
+/// Returns the discriminant associated with the first pattern that matches the given value.
+///
+/// Syntax: `discriminant_match!(` *expression* `, {` *pattern* [ `if` *guard* ] `=>` *discriminant* `,` ... `}` [ `,` *default* ] `)`
+///
+/// If no pattern matches, the macro evaluates to *default*, or `Default::default()` if it is omitted.
+/// This is useful for mapping values to integers for wire formats and FFI.
+///
+/// # Examples
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// enum Frame {
+///     Ping,
+///     Data(Vec<u8>),
+///     Close(u16),
+/// }
+///
+/// fn main() {
+///     let frame = Frame::Data(vec![1, 2, 3]);
+///     let tag: u8 = discriminant_match!(frame, {
+///         Frame::Ping => 1,
+///         Frame::Data(_) => 2,
+///         Frame::Close(_) => 3,
+///     });
+///     assert_eq!(tag, 2);
+/// }
+/// ```
+#[macro_export]
+macro_rules! discriminant_match {
+    ($expression:expr, { $($($pattern:pat)|+ $(if $ifguard:expr)? => $discriminant:expr),+ $(,)? } $(, $default:expr)?) => {
+        match $expression {
+            $($($pattern)|+ $(if $ifguard)? => $discriminant,)+
+            #[allow(unreachable_patterns)]
+            _ => $crate::discriminant_match!(@default $($default)?)
+        }
+    };
+    (@default $default:expr) => {
+        $default
+    };
+    (@default) => {
+        $crate::__private::Default::default()
+    };
+}
+
+/// Assert that a value satisfies at least one of a list of predicates.
+///
+/// Syntax: `assert_any_predicate!(` *value* `, [` *predicate* `,` ... `]` [ `,` *error message* ] `)`
+///
+/// Each predicate is called with a reference to the value, in order, until one returns true.
+/// If none do, this macro panics with a message that contains the value and the given error message, if any.
+/// This complements the pattern macros for conditions that are only known at runtime.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     let checks: Vec<fn(&i32) -> bool> = vec![|n| *n < 0, |n| n % 2 == 0];
+///     assert_any_predicate!(4, [checks[0], checks[1]], "expected a negative or even number");
+/// }
+/// ```
+#[macro_export]
+macro_rules! assert_any_predicate {
+    ($value:expr, [$($predicate:expr),+ $(,)?] $(, $($msg:tt)+)?) => {
+        match $value {
+            ref value => if !(false $(|| ($predicate)(value))+) {
+                panic!(
+                    "assertion failed: `{}` does not satisfy any of the predicates{}",
+                    $crate::__private::Value(value),
+                    $crate::assert_any_predicate!(@message $($($msg)+)?)
+                )
+            }
+        }
+    };
+    (@message $($msg:tt)+) => {
+        format_args!(": {}", format_args!($($msg)+))
+    };
+    (@message) => {
+        ""
+    };
+}
+
+/// Match the value inside a `Result::Ok` against a pattern, passing errors through.
+///
+/// Syntax: `and_then_match!(` *expression* `,` *pattern* `=>` *result* `, else` *error* `)`
+///
+/// The expression must be a `Result`. If it is `Ok` and its value matches *pattern*, the macro evaluates to
+/// `Ok(`*result*`)`. If it is `Ok` but the value does not match, it evaluates to `Err(`*error*`)`.
+/// An `Err` is passed through unchanged.
+///
+/// # Examples
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     let port = and_then_match!("8080".parse::<u16>().map_err(|e| e.to_string()), p if p >= 1024 => p, else "privileged port".to_string());
+///     assert_eq!(port, Ok(8080));
+/// }
+/// ```
+#[macro_export]
+macro_rules! and_then_match {
+    ($expression:expr, $($pattern:pat)|* $(if $ifguard:expr)? => $result:expr, else $error:expr) => {
+        match $expression {
+            $crate::__private::Result::Ok($($pattern)|*) $(if $ifguard)? => $crate::__private::Result::Ok($result),
+            #[allow(unreachable_patterns)]
+            $crate::__private::Result::Ok(_) => $crate::__private::Result::Err($error),
+            $crate::__private::Result::Err(error) => $crate::__private::Result::Err(error)
+        }
+    };
+}
+
+/// Assert at compile time that a constant expression matches a refutable pattern.
+///
+/// Syntax: `const_assert_matches!(` *expression* `,` *pattern* `)`
+///
+/// The assertion is evaluated in a `const` item, so both the expression and the guard must be const-evaluable.
+/// If the pattern does not match, compilation fails with a message that contains the expression and the pattern.
+/// The macro can be used both as an item and as a statement.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// enum Mode {
+///     Debug,
+///     Release,
+/// }
+///
+/// struct Config {
+///     mode: Mode,
+///     threads: usize,
+/// }
+///
+/// const CONFIG: Config = Config { mode: Mode::Release, threads: 4 };
+///
+/// const_assert_matches!(CONFIG.mode, Mode::Release);
+///
+/// fn main() {
+///     const_assert_matches!(CONFIG.threads, 1..=64);
+/// }
+/// ```
+///
+/// A mismatch is a compile error:
+///
+/// ```compile_fail
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// const_assert_matches!(Some(3), Some(0) | None);
+///
+/// fn main() {}
+/// ```
+#[macro_export]
+macro_rules! const_assert_matches {
+    ($expression:expr, $($pattern:pat)|* $(if $ifguard:expr)?) => {
+        const _: () = assert!(
+            $crate::matches!($expression, $($pattern)|* $(if $ifguard)?),
+            "{}",
+            concat!(
+                "assertion failed: `",
+                stringify!($expression),
+                "` does not match `",
+                stringify!($($pattern)|* $(if $ifguard)?),
+                "`"
+            )
+        );
+    };
+}
+
+/// Write the result into an out-parameter if the pattern matches with the given value.
+///
+/// Syntax: `match_into!(` *expression* `,` *pattern* `=>` *result* `,` *out* `)`
+///
+/// *out* must be a `&mut` reference. If *pattern* matches, *result* is written to `*`*out* and the macro
+/// evaluates to true; otherwise *out* is left untouched and the macro evaluates to false.
+/// This allows reusing a buffer across iterations of a loop.
+///
+/// # Examples
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     let mut last = 0;
+///     for value in &[Some(1), None, Some(3), None] {
+///         match_into!(*value, Some(n) => n, &mut last);
+///     }
+///     assert_eq!(last, 3);
+/// }
+/// ```
+#[macro_export]
+macro_rules! match_into {
+    ($expression:expr, $($pattern:pat)|* $(if $ifguard:expr)? => $result:expr, $out:expr) => {
+        match $expression {
+            $($pattern)|* $(if $ifguard)? => {
+                *$out = $result;
+                true
+            }
+            #[allow(unreachable_patterns)]
+            _ => false
+        }
+    };
+}
+
+/// Check if any window of a slice matches a slice pattern.
+///
+/// Syntax: `windows_matches!(` *slice* `,` *window length* `,` *pattern* `)`
+///
+/// The pattern is matched against each window of `slice.windows(`*window length*`)`, which are `&[T]`.
+/// Returns false if the slice is shorter than the window length.
+/// Like `slice::windows`, this panics if the window length is zero.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     let signal = [3, 0, 0, 0, 7];
+///     assert!(windows_matches!(signal, 3, [0, 0, 0]));
+///     assert!(!windows_matches!(signal, 2, [a, b] if a + b > 10));
+/// }
+/// ```
+#[macro_export]
+macro_rules! windows_matches {
+    ($slice:expr, $window_len:expr, $($pattern:pat)|* $(if $ifguard:expr)?) => {
+        $slice.windows($window_len).any(|window| $crate::matches!(window, $($pattern)|* $(if $ifguard)?))
+    };
+}
+
+/// Evaluate to the result if the pattern matches, otherwise return `None` from the enclosing function.
+///
+/// Syntax: `question_match!(` *expression* `,` *pattern* `=>` *result* `)`
+///
+/// This is like the `?` operator for an arbitrary pattern, in functions returning `Option`.
+///
+/// # Examples
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// enum Node {
+///     Leaf(u32),
+///     Branch(Box<Node>, Box<Node>),
+/// }
+///
+/// fn leftmost_pair(node: &Node) -> Option<(u32, u32)> {
+///     let (left, right) = question_match!(node, Node::Branch(left, right) => (left, right));
+///     let a = question_match!(**left, Node::Leaf(a) => a);
+///     let b = question_match!(**right, Node::Leaf(b) => b);
+///     Some((a, b))
+/// }
+///
+/// fn main() {
+///     let tree = Node::Branch(Box::new(Node::Leaf(1)), Box::new(Node::Leaf(2)));
+///     assert_eq!(leftmost_pair(&tree), Some((1, 2)));
+///     assert_eq!(leftmost_pair(&Node::Leaf(3)), None);
+/// }
+/// ```
+#[macro_export]
+macro_rules! question_match {
+    ($expression:expr, $($pattern:pat)|* $(if $ifguard:expr)? => $result:expr) => {
+        match $expression {
+            $($pattern)|* $(if $ifguard)? => $result,
+            #[allow(unreachable_patterns)]
+            _ => return $crate::__private::Option::None
+        }
+    };
+}
+
+/// Records which arms of a `cov_matches!` have been hit.
+///
+/// A tracker supports matches with up to 64 arms.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// use matches2::MatchCoverage;
+///
+/// fn main() {
+///     let mut coverage = MatchCoverage::new();
+///     for input in &[0, 5, 100] {
+///         cov_matches!(&mut coverage, *input, {
+///             0 => "zero",
+///             1..=9 => "small",
+///             _ => "large",
+///         });
+///     }
+///     coverage.assert_all_hit();
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct MatchCoverage {
+    arms: usize,
+    hit: u64,
+}
+
+impl MatchCoverage {
+    /// Create a tracker with no arms hit.
+    pub fn new() -> MatchCoverage {
+        MatchCoverage::default()
+    }
+
+    #[doc(hidden)]
+    pub fn set_arms(&mut self, arms: usize) {
+        assert!(arms <= 64, "MatchCoverage supports at most 64 arms, got {}", arms);
+        self.arms = self.arms.max(arms);
+    }
+
+    #[doc(hidden)]
+    pub fn record(&mut self, arm: usize) {
+        self.hit |= 1 << arm;
+    }
+
+    /// Return whether the arm with the given index has been hit.
+    pub fn is_hit(&self, arm: usize) -> bool {
+        arm < 64 && self.hit & (1 << arm) != 0
+    }
+
+    /// Assert that every arm has been hit at least once.
+    ///
+    /// Otherwise, this panics with a message listing the indices of the arms that were never hit.
+    pub fn assert_all_hit(&self) {
+        if (0..self.arms).any(|arm| !self.is_hit(arm)) {
+            panic!("assertion failed: match arms never hit: {}", __private::Unhit(self));
+        }
+    }
+}
+
+/// Match an expression against a list of arms like `match`, recording which arm was hit.
+///
+/// Syntax: `cov_matches!(` *coverage* `,` *expression* `, {` *pattern* [ `if` *guard* ] `=>` *result* `,` ... `})`
+///
+/// *coverage* must be a `&mut MatchCoverage`. The macro evaluates to the result of the arm that matched,
+/// so, like a `match`, the arms must be exhaustive. The arms are numbered from 0 in the order they are written.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// use matches2::MatchCoverage;
+///
+/// fn main() {
+///     let mut coverage = MatchCoverage::new();
+///     let sign = cov_matches!(&mut coverage, -3, {
+///         n if n < 0 => -1,
+///         0 => 0,
+///         _ => 1,
+///     });
+///     assert_eq!(sign, -1);
+///     assert!(coverage.is_hit(0));
+///     assert!(!coverage.is_hit(1));
+/// }
+/// ```
+#[macro_export]
+macro_rules! cov_matches {
+    ($coverage:expr, $expression:expr, { $($($pattern:pat)|+ $(if $ifguard:expr)? => $result:expr),+ $(,)? }) => {{
+        let coverage: &mut $crate::MatchCoverage = $coverage;
+        coverage.set_arms(<[()]>::len(&[$($crate::cov_matches!(@unit $result)),+]));
+        $crate::cov_matches!(
+            @arms coverage, $expression,
+            [
+                0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31
+                32 33 34 35 36 37 38 39 40 41 42 43 44 45 46 47 48 49 50 51 52 53 54 55 56 57 58 59 60 61 62 63
+            ],
+            [],
+            $($($pattern)|+ $(if $ifguard)? => $result,)+
+        )
+    }};
+    (@arms $coverage:ident, $expression:expr, [$index:tt $($indices:tt)*], [$($arms:tt)*],
+        $($pattern:pat)|+ $(if $ifguard:expr)? => $result:expr, $($rest:tt)*) => {
+        $crate::cov_matches!(
+            @arms $coverage, $expression, [$($indices)*],
+            [$($arms)* $($pattern)|+ $(if $ifguard)? => {
+                $coverage.record($index);
+                $result
+            }],
+            $($rest)*
+        )
+    };
+    (@arms $coverage:ident, $expression:expr, [$($indices:tt)*], [$($arms:tt)*],) => {
+        match $expression {
+            $($arms)*
+        }
+    };
+    (@unit $result:expr) => {
+        ()
+    };
+}
+
+/// Returns `Option::Some` with the result if the pattern matches with the borrowed value, or `Option::None` otherwise.
+///
+/// Syntax: `clone_match!(&` *place* `,` *pattern* `=>` *result* `)`
+///
+/// The pattern is matched against `&`*place*, so its bindings are references and the value is not consumed.
+/// *result* can clone out the parts it needs, e.g. to capture snapshots.
+///
+/// # Examples
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// struct User {
+///     name: String,
+///     admin: bool,
+/// }
+///
+/// fn main() {
+///     let user = User { name: "root".to_string(), admin: true };
+///     let name = clone_match!(&user, User { name, admin: true } => name.clone());
+///     assert_eq!(name.as_deref(), Some("root"));
+///     assert_eq!(user.name, "root");
+/// }
+/// ```
+#[macro_export]
+macro_rules! clone_match {
+    (&$place:expr, $($pattern:pat)|* $(if $ifguard:expr)? => $result:expr) => {
+        match &$place {
+            $($pattern)|* $(if $ifguard)? => Some($result),
+            #[allow(unreachable_patterns)]
+            _ => None
+        }
+    };
+}
+
+/// Assert that an enum value is of a given variant, regardless of its fields.
+///
+/// Syntax: `assert_variant!(` *expression* `,` *variant* `)`
+///
+/// *variant* is the path of a unit, tuple or struct variant.
+/// On failure the panic message names the expected and the actual variant instead of showing the whole value.
+/// The actual variant name is taken from the `{:?}` output of the value, so both variants are named as `{:?}`
+/// does, without the `r#` prefix of raw identifiers. Unlike this, the messages of the macros that show a
+/// pattern show it as written, including `r#`.
+///
+/// # Examples
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// #[derive(Debug)]
+/// enum Shape {
+///     Circle(f64),
+///     Rect { width: f64, height: f64 },
+/// }
+///
+/// fn main() {
+///     assert_variant!(Shape::Circle(1.0), Shape::Circle);
+///     assert_variant!(Shape::Rect { width: 1.0, height: 2.0 }, Shape::Rect);
+/// }
+/// ```
+#[macro_export]
+macro_rules! assert_variant {
+    ($expression:expr, $variant:path $(,)?) => {
+        match $expression {
+            ref value => {
+                if !$crate::matches!(*value, $variant { .. }) {
+                    panic!(
+                        "assertion failed: expected variant `{}`, got `{}`",
+                        $crate::__private::VariantPath(stringify!($variant)),
+                        $crate::__private::Variant(stringify!($variant), value)
+                    )
+                }
+            }
+        }
+    };
+}
+
+/// Check if the leading elements of a string or slice match a list of patterns, ignoring the rest.
+///
+/// Syntax: `starts_with_match!(` *expression* `, [` *pattern* `,` ... `])`
+///
+/// Strings are matched by their bytes. Unlike indexing a prefix out of the value first,
+/// this returns `false` rather than panicking if the value is shorter than the prefix.
+///
+/// # Examples
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     assert!(starts_with_match!("Hi there", [b'H', b'i']));
+///     assert!(starts_with_match!([1, 2, 3], [1, 2..=5]));
+///     assert!(!starts_with_match!("H", [b'H', b'i']));
+/// }
+/// ```
+#[macro_export]
+macro_rules! starts_with_match {
+    ($expression:expr, [$($($pattern:pat)|+),* $(,)?]) => {
+        match $crate::__private::Elements::elements(&$expression) {
+            [$($($pattern)|+,)* ..] => true,
+            #[allow(unreachable_patterns)]
+            _ => false
+        }
+    };
+}
+
+/// An iterator that yields the results of a closure for the items it returns `Some` for,
+/// usable where a macro is not, e.g. in generic code.
+///
+/// It is created by [`filter_match_fn`] and used by `filter_matches!`.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// use matches2::{filter_match_fn, MatchFilter};
+///
+/// fn numbers<I: Iterator<Item = Result<u32, String>>>(iter: I) -> MatchFilter<I, fn(Result<u32, String>) -> Option<u32>> {
+///     filter_match_fn(iter, |item| option_match!(item, Ok(n) => n))
+/// }
+///
+/// fn main() {
+///     let items = vec![Ok(1), Err("x".to_string()), Ok(2)];
+///     assert_eq!(numbers(items.into_iter()).collect::<Vec<_>>(), [1, 2]);
+/// }
+/// ```
+#[derive(Clone, Debug)]
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct MatchFilter<I, F> {
+    iter: I,
+    f: F,
+}
+
+impl<I, F, B> Iterator for MatchFilter<I, F>
+where
+    I: Iterator,
+    F: FnMut(I::Item) -> Option<B>,
+{
+    type Item = B;
+
+    fn next(&mut self) -> Option<B> {
+        let f = &mut self.f;
+        self.iter.find_map(f)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.iter.size_hint().1)
+    }
+}
+
+/// Create a [`MatchFilter`] over an iterable, yielding the results of the closure for the items it
+/// returns `Some` for.
+pub fn filter_match_fn<I, F, B>(iter: I, f: F) -> MatchFilter<I::IntoIter, F>
+where
+    I: IntoIterator,
+    F: FnMut(I::Item) -> Option<B>,
+{
+    MatchFilter { iter: iter.into_iter(), f }
+}
+
+/// Iterate over the results for the items of an iterable that match a pattern, skipping the rest.
+///
+/// Syntax: `filter_matches!(` *iterable* `,` *pattern* [ `if` *guard* ] `=>` *result* `)`
+///
+/// The macro evaluates to a [`MatchFilter`].
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     let items = vec![Some(1), None, Some(20), Some(3)];
+///     let small: Vec<i32> = filter_matches!(items, Some(n) if n < 10 => n * 2).collect();
+///     assert_eq!(small, [2, 6]);
+/// }
+/// ```
+#[macro_export]
+macro_rules! filter_matches {
+    ($iterable:expr, $($pattern:pat)|+ $(if $ifguard:expr)? => $result:expr) => {
+        $crate::filter_match_fn($iterable, |item| $crate::option_match!(item, $($pattern)|+ $(if $ifguard)? => $result))
+    };
+}
+
+/// Assert that an expression matches exactly one of a list of patterns.
+///
+/// Syntax: `assert_exactly_one_matches!(` *expression* `, [` *pattern* [ `if` *guard* ] `,` ... `])`
+///
+/// Every pattern is tried, and the assertion fails if none or more than one of them match.
+/// The panic message reports the value and how many patterns matched.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     let code = 404;
+///     assert_exactly_one_matches!(code, [100..=199, 200..=299, 400..=499, 500..=599]);
+/// }
+/// ```
+#[macro_export]
+macro_rules! assert_exactly_one_matches {
+    ($expression:expr, [$($($pattern:pat)|+ $(if $ifguard:expr)?),+ $(,)?]) => {
+        match $expression {
+            ref value => {
+                let count = 0usize $(+ $crate::matches!(*value, $($pattern)|+ $(if $ifguard)?) as usize)+;
+                if count != 1 {
+                    panic!(
+                        "assertion failed: expected exactly one pattern of `[{}]` to match `{}`, but {} matched",
+                        stringify!($($($pattern)|+ $(if $ifguard)?),+),
+                        $crate::__private::Value(value),
+                        count
+                    )
+                }
+            }
+        }
+    };
+}
+
+/// Returns the sort key of the first pattern that matches the given value.
+///
+/// Syntax: `order_match!(` *expression* `, {` *pattern* [ `if` *guard* ] `=>` *key* `,` ... `})`
+///
+/// Like `labeled_match!` with the pattern first, but the arms must be exhaustive so a key is always returned.
+/// This is meant for `sort_by_key` and similar, where values are ordered by variant priority.
+///
+/// # Examples
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// #[derive(Debug, PartialEq)]
+/// enum Level {
+///     Info,
+///     Warn,
+///     Error(u8),
+/// }
+///
+/// fn main() {
+///     let mut levels = vec![Level::Info, Level::Error(1), Level::Warn];
+///     levels.sort_by_key(|level| order_match!(level, {
+///         Level::Error(_) => 0,
+///         Level::Warn => 1,
+///         Level::Info => 2,
+///     }));
+///     assert_eq!(levels, [Level::Error(1), Level::Warn, Level::Info]);
+/// }
+/// ```
+#[macro_export]
+macro_rules! order_match {
+    ($expression:expr, { $($($pattern:pat)|+ $(if $ifguard:expr)? => $key:expr),+ $(,)? }) => {
+        match $expression {
+            $($($pattern)|+ $(if $ifguard)? => $key,)+
+        }
+    };
+}
+
+/// Assert that no element of an iterable matches a pattern.
+///
+/// Syntax: `assert_none_match!(` *iterable* `,` *pattern* [ `if` *guard* ] `)`
+///
+/// The panic message reports the index and the value of the first element that matched.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     let results: Vec<Result<u32, String>> = vec![Ok(1), Ok(2)];
+///     assert_none_match!(results.clone(), Err(_));
+/// }
+/// ```
+#[macro_export]
+macro_rules! assert_none_match {
+    ($iterable:expr, $($pattern:pat)|+ $(if $ifguard:expr)?) => {
+        for (index, ref item) in $crate::__private::IntoIterator::into_iter($iterable).enumerate() {
+            if $crate::matches!(*item, $($pattern)|+ $(if $ifguard)?) {
+                panic!(
+                    "assertion failed: element {} (`{}`) unexpectedly matched `{}`",
+                    index,
+                    $crate::__private::Value(item),
+                    stringify!($($pattern)|+ $(if $ifguard)?)
+                )
+            }
+        }
+    };
+}
+
+/// Unwrap a pattern like `unwrap_match!` with debug assertions, or fall back to a default without them.
+///
+/// Syntax: `soft_assert_matches!(` *expression* `,` *pattern* [ `if` *guard* ] `=>` *result* `,` *default* `)`
+///
+/// While debug assertions are enabled, a mismatch panics like `unwrap_match!`.
+/// Otherwise, the mismatch is reported on stderr and the macro evaluates to *default*.
+/// Without the `std` feature, nothing is reported.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     let port: Option<u16> = Some(8080);
+///     assert_eq!(soft_assert_matches!(port, Some(p) if p > 1024 => p, 8000), 8080);
+/// }
+/// ```
+#[cfg(debug_assertions)]
+#[macro_export]
+macro_rules! soft_assert_matches {
+    ($expression:expr, $($pattern:pat)|+ $(if $ifguard:expr)? => $result:expr, $default:expr $(,)?) => {
+        $crate::unwrap_match!($expression, $($pattern)|+ $(if $ifguard)? => $result)
+    };
+}
+
+/// Unwrap a pattern like `unwrap_match!` with debug assertions, or fall back to a default without them.
+#[cfg(not(debug_assertions))]
+#[macro_export]
+macro_rules! soft_assert_matches {
+    ($expression:expr, $($pattern:pat)|+ $(if $ifguard:expr)? => $result:expr, $default:expr $(,)?) => {
+        match $expression {
+            $($pattern)|+ $(if $ifguard)? => $result,
+            #[allow(unreachable_patterns)]
+            ref value => {
+                $crate::__private::report(format_args!(
+                    "{}",
+                    $crate::__private::Mismatch(stringify!($expression), value, stringify!($($pattern)|+ $(if $ifguard)?))
+                ));
+                $default
+            }
+        }
+    };
+}
+
+/// Check if an expression is `Option::None`.
+///
+/// Syntax: `is_none!(` *expression* `)`
+///
+/// Like `is_some!`, `is_ok!` and `is_err!`, this is a shorthand for `matches!` that works on any expression
+/// and reads well in conditionals.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     let words = ["a", "b"];
+///     assert!(is_none!(words.iter().position(|w| *w == "c")));
+/// }
+/// ```
+#[macro_export]
+macro_rules! is_none {
+    ($expression:expr $(,)?) => {
+        $crate::matches!($expression, $crate::__private::Option::None)
+    };
+}
+
+/// Check if an expression is `Option::Some`.
+///
+/// Syntax: `is_some!(` *expression* `)`
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     assert!(is_some!("42".parse::<u8>().ok()));
+/// }
+/// ```
+#[macro_export]
+macro_rules! is_some {
+    ($expression:expr $(,)?) => {
+        $crate::matches!($expression, $crate::__private::Option::Some(_))
+    };
+}
+
+/// Check if an expression is `Result::Ok`.
+///
+/// Syntax: `is_ok!(` *expression* `)`
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     assert!(is_ok!("42".parse::<u8>()));
+/// }
+/// ```
+#[macro_export]
+macro_rules! is_ok {
+    ($expression:expr $(,)?) => {
+        $crate::matches!($expression, $crate::__private::Result::Ok(_))
+    };
+}
+
+/// Check if an expression is `Result::Err`.
+///
+/// Syntax: `is_err!(` *expression* `)`
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     assert!(is_err!("256".parse::<u8>()));
+/// }
+/// ```
+#[macro_export]
+macro_rules! is_err {
+    ($expression:expr $(,)?) => {
+        $crate::matches!($expression, $crate::__private::Result::Err(_))
+    };
+}
+
+/// A builder for assertions made of several checks on a value, as an alternative to nesting macros.
+///
+/// Checks are closures returning whether the value passed. Once a check fails, the following ones are skipped,
+/// and [`assert`](MatchAssertion::assert) panics with a message naming the failed check and showing the value.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// use matches2::MatchAssertion;
+///
+/// fn main() {
+///     let response: Result<(u16, &str), ()> = Ok((200, "hello"));
+///     let response = MatchAssertion::new(response)
+///         .matches(|r| matches!(*r, Ok((200..=299, _))))
+///         .and_then(|r| matches!(*r, Ok((_, body)) if !body.is_empty()))
+///         .assert("bad response");
+///     assert_eq!(response, Ok((200, "hello")));
+/// }
+/// ```
+#[derive(Clone, Debug)]
+#[must_use = "a `MatchAssertion` does nothing unless `assert` is called"]
+pub struct MatchAssertion<T> {
+    value: T,
+    checks: usize,
+    failed: Option<usize>,
+}
+
+impl<T> MatchAssertion<T> {
+    /// Start an assertion about a value, with no checks yet.
+    pub fn new(value: T) -> MatchAssertion<T> {
+        MatchAssertion { value, checks: 0, failed: None }
+    }
+
+    /// Add a check, usually a `matches!` on the value.
+    pub fn matches<F: FnOnce(&T) -> bool>(mut self, check: F) -> MatchAssertion<T> {
+        self.checks += 1;
+        if self.failed.is_none() && !check(&self.value) {
+            self.failed = Some(self.checks);
+        }
+        self
+    }
+
+    /// Add a check that only runs if all the previous ones passed.
+    pub fn and_then<F: FnOnce(&T) -> bool>(self, check: F) -> MatchAssertion<T> {
+        self.matches(check)
+    }
+
+    /// Return whether all the checks passed.
+    pub fn passed(&self) -> bool {
+        self.failed.is_none()
+    }
+
+    /// Assert that all the checks passed and return the value.
+    ///
+    /// Otherwise, this panics with a message like ``assertion failed: msg: check 2 of 3 failed for `value` ``.
+    pub fn assert(self, msg: &str) -> T
+    where
+        T: core::fmt::Debug,
+    {
+        if let Some(failed) = self.failed {
+            panic!(
+                "assertion failed: {}: check {} of {} failed for `{}`",
+                msg,
+                failed,
+                self.checks,
+                __private::Value(&self.value)
+            );
+        }
+        self.value
+    }
+}
+
+/// Match the leading elements of a slice against a list of patterns, returning the rest of the slice.
+///
+/// Syntax: `split_match!(` *slice* `, [` *pattern* `,` ... `] =>` *result* `)`
+///
+/// Returns `Option::Some((`*result*`, rest))` if the prefix matches, where `rest` is the tail after the prefix,
+/// or `Option::None` if it does not match or the slice is too short. *slice* may be anything that coerces
+/// to a `&[T]`, and the bindings in the patterns are references into it.
+///
+/// # Examples
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     let input: &[u8] = b"\x7fELF\x02rest";
+///     let (class, rest) = split_match!(input, [0x7f, b'E', b'L', b'F', class] => *class).unwrap();
+///     assert_eq!(class, 2);
+///     assert_eq!(rest, b"rest");
+/// }
+/// ```
+#[macro_export]
+macro_rules! split_match {
+    ($slice:expr, [$($($pattern:pat)|+),* $(,)?] => $result:expr) => {
+        match $slice {
+            slice => {
+                let slice: &[_] = slice;
+                match slice {
+                    [$($($pattern)|+,)* rest @ ..] => Some(($result, rest)),
+                    #[allow(unreachable_patterns)]
+                    _ => None
+                }
+            }
+        }
+    };
+}
+
+/// Assert that some fields of a struct match patterns, ignoring the other fields.
+///
+/// Syntax: `assert_matches_ignoring!(` *expression* `,` *path* `{` *field* `:` *pattern* `,` ... `, .. })`
+///
+/// The `..` is required, to make clear that the unlisted fields are ignored. If a listed field does not match,
+/// the panic message names only the listed fields that did not match, e.g. ``mismatched fields: `status` ``.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// #[derive(Debug)]
+/// struct Event {
+///     kind: &'static str,
+///     count: u32,
+///     timestamp: u64,
+/// }
+///
+/// fn main() {
+///     let event = Event { kind: "click", count: 2, timestamp: 1_700_000_000 };
+///     assert_matches_ignoring!(event, Event { kind: "click", count: 1..=3, .. });
+/// }
+/// ```
+#[macro_export]
+macro_rules! assert_matches_ignoring {
+    ($expression:expr, $path:path { $($field:ident : $pattern:pat),+ , .. }) => {
+        $crate::assert_matches_partial!(@fields fields, $expression, $path, [$($field: $pattern),+])
+    };
+}
+
+/// Check that an expression matches a refutable pattern in a `proptest` test, without panicking.
+///
+/// Syntax: `prop_assert_matches!(` *expression* `,` *pattern* [ `if` *guard* ] `)`
+///
+/// Evaluates to `Ok(())` if the pattern matches, or to `Err(TestCaseError::fail(..))` with the default
+/// `assert_matches!` message otherwise, so proptest can shrink the failing input.
+/// This macro requires the `proptest` feature.
+///
+/// # Examples
+///
+#[cfg_attr(feature = "proptest", doc = "```")]
+#[cfg_attr(not(feature = "proptest"), doc = "```ignore")]
+/// #[macro_use]
+/// extern crate matches2;
+/// #[macro_use]
+/// extern crate proptest;
+///
+/// proptest! {
+///     fn parses_small_numbers(n in 0u32..1000) {
+///         prop_assert_matches!(n.to_string().parse::<u16>(), Ok(m) if u32::from(m) == n)?;
+///     }
+/// }
+///
+/// fn main() {
+///     parses_small_numbers();
+/// }
+/// ```
+#[cfg(feature = "proptest")]
+#[macro_export]
+macro_rules! prop_assert_matches {
+    ($expression:expr, $($pattern:pat)|+ $(if $ifguard:expr)?) => {
+        match $expression {
+            $($pattern)|+ $(if $ifguard)? => $crate::__private::Result::Ok(()),
+            #[allow(unreachable_patterns)]
+            ref value => $crate::__private::Result::Err($crate::__private::proptest::test_runner::TestCaseError::fail($crate::__private::format!(
+                "{}",
+                $crate::__private::Mismatch(stringify!($expression), value, stringify!($($pattern)|+ $(if $ifguard)?))
+            )))
+        }
+    };
+}
+
+/// Assert that the number of elements of an iterable that match a pattern is within a range.
+///
+/// Syntax: `assert_match_count!(` *iterable* `,` *pattern* [ `if` *guard* ] `,` *range* `)`
+///
+/// *range* is any `RangeBounds<usize>`, e.g. `2..=4` or `3..`. The panic message reports the actual count.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     let results: Vec<Result<u8, ()>> = vec![Ok(1), Err(()), Ok(2), Ok(3)];
+///     assert_match_count!(&results, Ok(_), 2..=4);
+/// }
+/// ```
+#[macro_export]
+macro_rules! assert_match_count {
+    ($iterable:expr, $($pattern:pat)|+ $(if $ifguard:expr)?, $range:expr $(,)?) => {{
+        let count = $crate::__private::IntoIterator::into_iter($iterable)
+            .filter(|item| $crate::matches!(*item, $($pattern)|+ $(if $ifguard)?))
+            .count();
+        if !$crate::__private::RangeBounds::contains(&$range, &count) {
+            panic!(
+                "assertion failed: expected `{}` elements to match `{}`, but {} matched",
+                stringify!($range),
+                stringify!($($pattern)|+ $(if $ifguard)?),
+                count
+            )
+        }
+    }};
+}
+
+/// Check if an expression matches a refutable pattern, incrementing a `metrics` counter with the outcome.
+///
+/// Syntax: `counted_match!(` *counter name* `,` *expression* `,` *pattern* [ `if` *guard* ] `)`
+///
+/// Returns the same boolean as `matches!`, after incrementing the counter named by the string literal
+/// with an `outcome` label of `"match"` or `"mismatch"`, through `metrics::counter!` and the installed recorder.
+/// Both `metrics` 0.23 and 0.24 are supported, so that the counter reaches the recorder of the calling crate.
+/// This macro requires the `metrics` feature.
+///
+/// # Examples
+///
+#[cfg_attr(feature = "metrics", doc = "```")]
+#[cfg_attr(not(feature = "metrics"), doc = "```ignore")]
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     let request: Result<u16, ()> = Ok(200);
+///     if counted_match!("requests_ok", request, Ok(200..=299)) {
+///         println!("success");
+///     }
+/// }
+/// ```
+#[cfg(feature = "metrics")]
+#[macro_export]
+macro_rules! counted_match {
+    ($name:literal, $expression:expr, $($pattern:pat)|+ $(if $ifguard:expr)?) => {{
+        let matched = $crate::matches!($expression, $($pattern)|+ $(if $ifguard)?);
+        $crate::__private::metrics::counter!($name, "outcome" => if matched { "match" } else { "mismatch" }).increment(1);
+        matched
+    }};
+}
+
+/// Unwrap a pattern from a borrowed `Option` or `Result` of smart pointers, matching through the pointers.
+///
+/// Syntax: `unwrap_match_deref!(` [ `&` ] *place* `,` *pattern* [ `if` *guard* ] `=>` *result* `)`
+///
+/// The contents of *place*, e.g. an `Option<Box<T>>` or a `Result<Rc<T>, E>`, are borrowed through `Deref`
+/// before matching, so the bindings are `&T` and the container is not consumed.
+/// Like `unwrap_match!`, this panics with a default message if the pattern does not match.
+///
+/// # Examples
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// #[derive(Debug)]
+/// struct Node {
+///     value: u32,
+///     next: Option<Box<Node>>,
+/// }
+///
+/// fn main() {
+///     let list = Node { value: 1, next: Some(Box::new(Node { value: 2, next: None })) };
+///     let next: &Node = unwrap_match_deref!(list.next, Some(node) => node);
+///     assert_eq!(next.value, 2);
+///     assert!(list.next.is_some());
+/// }
+/// ```
+#[macro_export]
+macro_rules! unwrap_match_deref {
+    (&$place:expr, $($pattern:pat)|+ $(if $ifguard:expr)? => $result:expr) => {
+        $crate::unwrap_match!($crate::__private::DerefInner::deref_inner(&$place), $($pattern)|+ $(if $ifguard)? => $result)
+    };
+    ($place:expr, $($pattern:pat)|+ $(if $ifguard:expr)? => $result:expr) => {
+        $crate::unwrap_match!($crate::__private::DerefInner::deref_inner(&$place), $($pattern)|+ $(if $ifguard)? => $result)
+    };
+}
+
+/// Structural comparison of a value against an expected value, as used by `assert_matches_shape!`.
+///
+/// [`divergence`](Shape::divergence) returns `None` if the value has the shape of the expected one, or the path
+/// of the first divergence, e.g. `.payload.len`, with an empty path meaning the values themselves differ.
+/// Implementations for enums usually compare variants first and then their fields with [`field_divergence`],
+/// and may skip fields, or treat some expected values as sentinels that match anything.
+///
+/// This trait is implemented with `==` for primitive types and strings, element-wise for `Vec` and slices,
+/// and through references, `Option` and `Box`. It requires the `std` feature.
+///
+/// # Examples
+///
+/// ```
+/// use matches2::{field_divergence, Shape};
+///
+/// enum Packet {
+///     // An `id` of 0 in the expected value matches any id.
+///     Data { id: u32, payload: Vec<u8> },
+///     Ack,
+/// }
+///
+/// impl Shape for Packet {
+///     fn divergence(&self, expected: &Packet) -> Option<String> {
+///         match (self, expected) {
+///             (Packet::Data { id, payload }, Packet::Data { id: expected_id, payload: expected_payload }) => {
+///                 if *expected_id != 0 && id != expected_id {
+///                     return field_divergence("id", id, expected_id);
+///                 }
+///                 field_divergence("payload", payload, expected_payload)
+///             }
+///             (Packet::Ack, Packet::Ack) => None,
+///             _ => Some(String::new()),
+///         }
+///     }
+/// }
+///
+/// let actual = Packet::Data { id: 7, payload: vec![1, 2] };
+/// assert_eq!(actual.divergence(&Packet::Data { id: 0, payload: vec![1, 2] }), None);
+/// assert_eq!(actual.divergence(&Packet::Data { id: 0, payload: vec![1] }), Some(".payload.len".to_string()));
+/// ```
+#[cfg(feature = "std")]
+pub trait Shape {
+    /// Return the path of the first divergence from the expected value, or `None` if there is none.
+    fn divergence(&self, expected: &Self) -> Option<String>;
+}
+
+/// Return the divergence of a field of a [`Shape`], with the field name prepended to its path.
+#[cfg(feature = "std")]
+pub fn field_divergence<T: Shape + ?Sized>(name: &str, actual: &T, expected: &T) -> Option<String> {
+    actual.divergence(expected).map(|path| format!(".{}{}", name, path))
+}
+
+#[cfg(feature = "std")]
+macro_rules! impl_shape_eq {
+    ($($type:ty),+) => {
+        $(
+            impl Shape for $type {
+                fn divergence(&self, expected: &$type) -> Option<String> {
+                    if self == expected {
+                        None
+                    } else {
+                        Some(String::new())
+                    }
+                }
+            }
+        )+
+    };
+}
+
+#[cfg(feature = "std")]
+impl_shape_eq!(bool, char, i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64, str, String);
+
+#[cfg(feature = "std")]
+impl<T: Shape> Shape for [T] {
+    fn divergence(&self, expected: &[T]) -> Option<String> {
+        if self.len() != expected.len() {
+            return Some(".len".to_string());
+        }
+        self.iter()
+            .zip(expected)
+            .enumerate()
+            .find_map(|(index, (actual, expected))| actual.divergence(expected).map(|path| format!("[{}]{}", index, path)))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: Shape> Shape for Vec<T> {
+    fn divergence(&self, expected: &Vec<T>) -> Option<String> {
+        self[..].divergence(&expected[..])
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: Shape + ?Sized> Shape for &T {
+    fn divergence(&self, expected: &&T) -> Option<String> {
+        (**self).divergence(&**expected)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: Shape + ?Sized> Shape for Box<T> {
+    fn divergence(&self, expected: &Box<T>) -> Option<String> {
+        (**self).divergence(&**expected)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: Shape> Shape for Option<T> {
+    fn divergence(&self, expected: &Option<T>) -> Option<String> {
+        match (self, expected) {
+            (Some(actual), Some(expected)) => actual.divergence(expected),
+            (None, None) => None,
+            _ => Some(String::new()),
+        }
+    }
+}
+
+/// Assert that a value has the shape of an expected value, as defined by their [`Shape`] implementation.
+///
+/// Syntax: `assert_matches_shape!(` *actual* `,` *expected* `)`
+///
+/// The panic message shows both values and the path of the first divergence, e.g. ``at `.payload.len` ``.
+/// This macro requires the `std` feature.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     let actual = vec![Some(1), None];
+///     assert_matches_shape!(actual, vec![Some(1), None]);
+/// }
+/// ```
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! assert_matches_shape {
+    ($actual:expr, $expected:expr $(,)?) => {
+        match (&$actual, &$expected) {
+            (actual, expected) => {
+                if let Some(path) = $crate::Shape::divergence(actual, expected) {
+                    panic!(
+                        "assertion failed: `{}` does not have the shape of `{}`: diverges at `{}`",
+                        $crate::__private::Value(actual),
+                        $crate::__private::Value(expected),
+                        if path.is_empty() { "." } else { &path[..] }
+                    )
+                }
+            }
+        }
+    };
+}
+
+/// Iterate over the results for the items of an iterable that match a pattern, with the index of each item in scope.
+///
+/// Syntax: `enumerate_matches!(` *iterable* `, |` *index* `|` *pattern* [ `if` *guard* ] `=>` *result* `)`
+///
+/// Like `filter_matches!`, but *index* is bound to the position of the item in the iterable,
+/// and can be used in the guard and the result. The macro evaluates to a [`MatchFilter`].
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     let items = vec![Some('a'), Some('b'), None, Some('d'), Some('e')];
+///     let even: Vec<(usize, char)> = enumerate_matches!(items, |i| Some(c) if i % 2 == 0 => (i, c)).collect();
+///     assert_eq!(even, [(0, 'a'), (4, 'e')]);
+/// }
+/// ```
+#[macro_export]
+macro_rules! enumerate_matches {
+    ($iterable:expr, |$index:ident| $($pattern:pat)|+ $(if $ifguard:expr)? => $result:expr) => {
+        $crate::filter_match_fn(
+            $crate::__private::IntoIterator::into_iter($iterable).enumerate(),
+            |($index, item)| $crate::option_match!(item, $($pattern)|+ $(if $ifguard)? => $result)
+        )
+    };
+}
+
+/// The error of a value not matching a pattern, holding the pattern and the `{:?}` output of the value.
+///
+/// It implements `std::error::Error`, so mismatches can be propagated and wrapped like other errors,
+/// e.g. by `try_match_anyhow!`. It requires the `std` feature.
+///
+/// # Examples
+///
+/// ```
+/// use matches2::MatchError;
+///
+/// let error = MatchError::new("Some(_)", &None::<u8>);
+/// assert_eq!(error.to_string(), "`None` does not match `Some(_)`");
+/// ```
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MatchError {
+    pattern: &'static str,
+    value: String,
+}
+
+#[cfg(feature = "std")]
+impl MatchError {
+    /// Create an error for a value that does not match the given stringified pattern.
+    pub fn new<T: core::fmt::Debug + ?Sized>(pattern: &'static str, value: &T) -> MatchError {
+        MatchError { pattern, value: format!("{:?}", value) }
+    }
+
+    /// Return the pattern that did not match.
+    pub fn pattern(&self) -> &'static str {
+        self.pattern
+    }
+
+    /// Return the `{:?}` output of the value that did not match.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+}
+
+#[cfg(feature = "std")]
+impl core::fmt::Display for MatchError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "`{}` does not match `{}`", self.value, self.pattern)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MatchError {}
+
+/// Returns `Result::Ok` with the result if the pattern matches with the given value,
+/// or an `anyhow::Error` wrapping a [`MatchError`] otherwise.
+///
+/// Syntax: `try_match_anyhow!(` *expression* `,` *pattern* [ `if` *guard* ] `=>` *result* `)`
+///
+/// The macro evaluates to an `anyhow::Result`, so the mismatch can be given context with `anyhow::Context`
+/// and propagated with `?`. The [`MatchError`] can be recovered with `anyhow::Error::downcast_ref`.
+/// This macro requires the `anyhow` feature.
+///
+/// # Examples
+///
+#[cfg_attr(feature = "anyhow", doc = "```")]
+#[cfg_attr(not(feature = "anyhow"), doc = "```ignore")]
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// use anyhow::Context;
+///
+/// fn frame_length(frame: &[u8]) -> anyhow::Result<u8> {
+///     try_match_anyhow!(frame, [0xAA, length, ..] => *length).context("while parsing frame")
+/// }
+///
+/// fn main() {
+///     assert_eq!(frame_length(&[0xAA, 3]).unwrap(), 3);
+///     assert!(frame_length(&[0xBB]).is_err());
+/// }
+/// ```
+#[cfg(feature = "anyhow")]
+#[macro_export]
+macro_rules! try_match_anyhow {
+    ($expression:expr, $($pattern:pat)|+ $(if $ifguard:expr)? => $result:expr) => {
+        match $expression {
+            $($pattern)|+ $(if $ifguard)? => $crate::__private::Result::Ok($result),
+            #[allow(unreachable_patterns)]
+            ref value => $crate::__private::Result::Err($crate::__private::anyhow::Error::new(
+                $crate::MatchError::new(stringify!($($pattern)|+ $(if $ifguard)?), value)
+            ))
+        }
+    };
+}
+
+/// Assert that every element of a slice matches a pattern, and that all of them are the same enum variant.
+///
+/// Syntax: `assert_all_same_match!(` *slice* `,` *pattern* [ `if` *guard* ] `)`
+///
+/// Variants are compared with `mem::discriminant`. The panic message reports the index and the value of the
+/// first element that does not match, or that is not the same variant as the first element.
+/// *slice* may be anything with an `iter` method over references to the elements, e.g. an array or a `Vec`.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// #[derive(Debug)]
+/// enum Cell {
+///     Int(i64),
+///     Text(&'static str),
+/// }
+///
+/// fn main() {
+///     let column = [Cell::Int(1), Cell::Int(2)];
+///     assert_all_same_match!(column, Cell::Int(_) | Cell::Text(_));
+/// }
+/// ```
+#[macro_export]
+macro_rules! assert_all_same_match {
+    ($slice:expr, $($pattern:pat)|+ $(if $ifguard:expr)?) => {{
+        let mut first = None;
+        for (index, item) in $slice.iter().enumerate() {
+            if !$crate::matches!(*item, $($pattern)|+ $(if $ifguard)?) {
+                panic!(
+                    "assertion failed: element {} (`{}`) does not match `{}`",
+                    index,
+                    $crate::__private::Value(item),
+                    stringify!($($pattern)|+ $(if $ifguard)?)
+                )
+            }
+            match first {
+                None => first = Some(item),
+                Some(first) => {
+                    if $crate::__private::discriminant(first) != $crate::__private::discriminant(item) {
+                        panic!(
+                            "assertion failed: element {} (`{}`) is not the same variant as element 0 (`{}`)",
+                            index,
+                            $crate::__private::Value(item),
+                            $crate::__private::Value(first)
+                        )
+                    }
+                }
+            }
+        }
+    }};
+}
+
+/// Check if every element of an array matches a refutable pattern.
+///
+/// Syntax: `array_all_match!(` *array* `,` *pattern* [ `if` *guard* ] `)`
+///
+/// The array is taken by value and its elements are matched in place, in order, stopping at the first mismatch.
+/// The macro can be used in `const` contexts, e.g. for arrays of `Copy` elements.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// const LEVELS: [Option<u8>; 3] = [Some(1), Some(4), Some(2)];
+/// const VALID: bool = array_all_match!(LEVELS, Some(1..=5));
+///
+/// fn main() {
+///     assert!(VALID);
+///     assert!(!array_all_match!([Some(1), None], Some(_)));
+/// }
+/// ```
+#[macro_export]
+macro_rules! array_all_match {
+    ($array:expr, $($pattern:pat)|+ $(if $ifguard:expr)?) => {{
+        let array = $array;
+        let mut index = 0;
+        let mut all = true;
+        while index < array.len() {
+            if !$crate::matches!(array[index], $($pattern)|+ $(if $ifguard)?) {
+                all = false;
+                break;
+            }
+            index += 1;
+        }
+        all
+    }};
+}
+
+/// Decides what `unwrap_match_with!` evaluates to, when the pattern matches and when it does not.
+///
+/// This lets the same macro panic, return an `Option`, log, or recover with a fallback,
+/// depending on the sink it is given. [`PanicSink`] and [`OptionSink`] are provided.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// use matches2::MatchSink;
+/// use std::fmt::Debug;
+///
+/// struct Fallback(u32);
+///
+/// impl MatchSink<u32> for Fallback {
+///     type Output = u32;
+///
+///     fn on_match(&mut self, result: u32) -> u32 {
+///         result
+///     }
+///
+///     fn on_mismatch(&mut self, value: &dyn Debug, pattern: &'static str) -> u32 {
+///         eprintln!("`{:?}` does not match `{}`, using {}", value, pattern, self.0);
+///         self.0
+///     }
+/// }
+///
+/// fn main() {
+///     assert_eq!(unwrap_match_with!(Fallback(80), "x".parse::<u32>(), Ok(port) => port), 80);
+/// }
+/// ```
+pub trait MatchSink<T> {
+    /// The type `unwrap_match_with!` evaluates to.
+    type Output;
+
+    /// Called with the result if the pattern matched.
+    fn on_match(&mut self, result: T) -> Self::Output;
+
+    /// Called with the value and the stringified pattern if the pattern did not match.
+    fn on_mismatch(&mut self, value: &dyn core::fmt::Debug, pattern: &'static str) -> Self::Output;
+}
+
+impl<T, S: MatchSink<T> + ?Sized> MatchSink<T> for &mut S {
+    type Output = S::Output;
+
+    fn on_match(&mut self, result: T) -> S::Output {
+        (**self).on_match(result)
+    }
+
+    fn on_mismatch(&mut self, value: &dyn core::fmt::Debug, pattern: &'static str) -> S::Output {
+        (**self).on_mismatch(value, pattern)
+    }
+}
+
+/// A [`MatchSink`] that returns the result, or panics with the same message as `unwrap_match!`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PanicSink;
+
+impl<T> MatchSink<T> for PanicSink {
+    type Output = T;
+
+    fn on_match(&mut self, result: T) -> T {
+        result
+    }
+
+    fn on_mismatch(&mut self, value: &dyn core::fmt::Debug, pattern: &'static str) -> T {
+        panic!("assertion failed: `{}` does not match `{}`", __private::Value(value), pattern)
+    }
+}
+
+/// A [`MatchSink`] that returns `Option::Some` with the result, or `Option::None`, like `option_match!`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct OptionSink;
+
+impl<T> MatchSink<T> for OptionSink {
+    type Output = Option<T>;
+
+    fn on_match(&mut self, result: T) -> Option<T> {
+        Some(result)
+    }
+
+    fn on_mismatch(&mut self, _: &dyn core::fmt::Debug, _: &'static str) -> Option<T> {
+        None
+    }
+}
+
+/// Unwrap a pattern, letting a [`MatchSink`] decide what to do with the result or the mismatch.
+///
+/// Syntax: `unwrap_match_with!(` *sink* `,` *expression* `,` *pattern* [ `if` *guard* ] `=>` *result* `)`
+///
+/// The macro evaluates to *sink*`.on_match(`*result*`)` if the pattern matches, and to
+/// *sink*`.on_mismatch(&value, pattern)` otherwise. *sink* is borrowed mutably, so it can be a variable
+/// that records mismatches across calls.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// use matches2::{OptionSink, PanicSink};
+///
+/// fn main() {
+///     assert_eq!(unwrap_match_with!(PanicSink, Some(3), Some(n) => n * 2), 6);
+///     assert_eq!(unwrap_match_with!(OptionSink, None::<u8>, Some(n) => n), None);
+/// }
+/// ```
+#[macro_export]
+macro_rules! unwrap_match_with {
+    ($sink:expr, $expression:expr, $($pattern:pat)|+ $(if $ifguard:expr)? => $result:expr) => {
+        match $expression {
+            $($pattern)|+ $(if $ifguard)? => $crate::MatchSink::on_match(&mut $sink, $result),
+            #[allow(unreachable_patterns)]
+            ref value => $crate::MatchSink::on_mismatch(&mut $sink, value, stringify!($($pattern)|+ $(if $ifguard)?))
+        }
+    };
+}
+
+/// Check if a `serde_json::Value` matches a JSON-like pattern.
+///
+/// Syntax: `json_matches!(` *value* `,` *json pattern* `)`
+///
+/// A JSON pattern is one of:
+///
+/// - `_`, which matches any value;
+/// - `null`;
+/// - a literal, such as `"ping"`, `3` or `true`, compared with `==`;
+/// - a parenthesized expression, such as `(-1)` or `(name)`, compared with `==`;
+/// - an object `{ "key": pattern, ... }`, which matches objects that have all the keys with matching values,
+///   and any other keys;
+/// - an array `[pattern, ...]`, which matches arrays that start with matching elements.
+///
+/// Numbers are compared with `serde_json`'s own `PartialEq` implementations: an integer such as `3` only matches
+/// integers, while a float such as `3.0` matches any number with the same value.
+/// This macro requires the `serde_json` feature.
+///
+/// # Examples
+///
+#[cfg_attr(feature = "serde_json", doc = "```")]
+#[cfg_attr(not(feature = "serde_json"), doc = "```ignore")]
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     let message = serde_json::json!({ "type": "ping", "id": 7, "tags": ["a", "b"] });
+///     assert!(json_matches!(message, { "type": "ping", "id": _, "tags": ["a"] }));
+///     assert!(!json_matches!(message, { "type": "pong" }));
+/// }
+/// ```
+#[cfg(feature = "serde_json")]
+#[macro_export]
+macro_rules! json_matches {
+    (@value $value:ident, _) => {{
+        let _ = $value;
+        true
+    }};
+    (@value $value:ident, null) => {
+        $value.is_null()
+    };
+    (@value $value:ident, { $($key:literal : $pattern:tt),* $(,)? }) => {
+        match $value.as_object() {
+            $crate::__private::Option::Some(object) => true $(&& match object.get($key) {
+                $crate::__private::Option::Some(field) => $crate::json_matches!(@value field, $pattern),
+                $crate::__private::Option::None => false
+            })*,
+            $crate::__private::Option::None => false
+        }
+    };
+    (@value $value:ident, [ $($pattern:tt),* $(,)? ]) => {
+        match $value.as_array() {
+            $crate::__private::Option::Some(array) => {
+                let mut items = array.iter();
+                true $(&& match items.next() {
+                    $crate::__private::Option::Some(item) => $crate::json_matches!(@value item, $pattern),
+                    $crate::__private::Option::None => false
+                })*
+            }
+            $crate::__private::Option::None => false
+        }
+    };
+    (@value $value:ident, ( $expected:expr )) => {
+        *$value == $expected
+    };
+    (@value $value:ident, $expected:literal) => {
+        *$value == $expected
+    };
+    ($value:expr, $pattern:tt) => {
+        match &$value {
+            value => {
+                let value: &$crate::__private::serde_json::Value = value;
+                $crate::json_matches!(@value value, $pattern)
+            }
+        }
+    };
+}
+
+/// Assert that a closure returns within a timeout, and that the returned value matches a refutable pattern.
+///
+/// Syntax: `assert_matches_within!(` *closure* `,` *timeout* `,` *pattern* [ `if` *guard* ] `)`
+///
+/// The closure is run on a new thread, so it must be `Send + 'static`, and *timeout* is a `Duration`.
+/// If it does not return in time, this panics with a "timed out" message; the thread is left running.
+/// Otherwise, a mismatch panics like `assert_matches!`. This macro requires the `std` feature.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// use std::time::Duration;
+///
+/// fn main() {
+///     assert_matches_within!(|| "42".parse::<u8>(), Duration::from_secs(5), Ok(42));
+/// }
+/// ```
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! assert_matches_within {
+    ($closure:expr, $timeout:expr, $($pattern:pat)|+ $(if $ifguard:expr)?) => {{
+        let timeout: $crate::__private::Duration = $timeout;
+        let closure = $closure;
+        let (sender, receiver) = $crate::__private::mpsc::channel();
+        $crate::__private::thread::spawn(move || {
+            let _ = sender.send(closure());
+        });
+        match receiver.recv_timeout(timeout) {
+            $crate::__private::Result::Ok($($pattern)|+) $(if $ifguard)? => (),
+            #[allow(unreachable_patterns)]
+            $crate::__private::Result::Ok(ref value) => panic!(
+                "assertion failed: `{}` does not match `{}`",
+                $crate::__private::Value(value),
+                stringify!($($pattern)|+ $(if $ifguard)?)
+            ),
+            $crate::__private::Result::Err($crate::__private::mpsc::RecvTimeoutError::Timeout) => panic!(
+                "assertion failed: `{}` timed out after {:?}",
+                stringify!($closure),
+                timeout
+            ),
+            $crate::__private::Result::Err($crate::__private::mpsc::RecvTimeoutError::Disconnected) => panic!(
+                "assertion failed: `{}` panicked",
+                stringify!($closure)
+            ),
+        }
+    }};
+}
+
+/// Returns the result for the `n`-th item of an iterable that matches a pattern, counting from 0,
+/// or `Option::None` if fewer items match.
+///
+/// Syntax: `nth_match!(` *iterable* `,` *n* `,` *pattern* [ `if` *guard* ] `=>` *result* `)`
+///
+/// The iteration stops as soon as the item is found.
+///
+/// # Examples
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     let tokens = ["let", "x", "=", "1", "+", "2"];
+///     assert_eq!(nth_match!(tokens.iter(), 1, s if s.parse::<u8>().is_ok() => *s), Some("2"));
+///     assert_eq!(nth_match!(tokens.iter(), 2, s if s.parse::<u8>().is_ok() => *s), None);
+/// }
+/// ```
+#[macro_export]
+macro_rules! nth_match {
+    ($iterable:expr, $n:expr, $($pattern:pat)|+ $(if $ifguard:expr)? => $result:expr) => {
+        $crate::filter_match_fn($iterable, |item| $crate::option_match!(item, $($pattern)|+ $(if $ifguard)? => $result)).nth($n)
+    };
+}
+
+/// Assert at compile time that a list of patterns covers every value of a type.
+///
+/// Syntax: `assert_patterns_exhaustive!(` *type* `, [` *pattern* `,` ... `])`
+///
+/// The patterns are expanded into the arms of a `match` on a value of *type*, without a wildcard arm,
+/// in a function that is never called. If they do not cover every variant, e.g. after a variant is added to an enum,
+/// compilation fails with the usual non-exhaustive patterns error.
+/// Guards are not allowed, since the compiler does not consider them for exhaustiveness.
+/// The macro can be used both as an item and as a statement.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// enum Signal {
+///     Start,
+///     Stop,
+///     Retry(u8),
+/// }
+///
+/// assert_patterns_exhaustive!(Signal, [Signal::Start | Signal::Stop, Signal::Retry(0), Signal::Retry(_)]);
+///
+/// fn main() {}
+/// ```
+///
+/// A missing variant is a compile error:
+///
+/// ```compile_fail
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// enum Signal {
+///     Start,
+///     Stop,
+///     Retry(u8),
+/// }
+///
+/// assert_patterns_exhaustive!(Signal, [Signal::Start, Signal::Retry(_)]);
+///
+/// fn main() {}
+/// ```
+#[macro_export]
+macro_rules! assert_patterns_exhaustive {
+    ($type:ty, [$($($pattern:pat)|+),+ $(,)?]) => {
+        const _: () = {
+            #[allow(dead_code, unused_variables)]
+            fn exhaustive(value: $type) {
+                match value {
+                    $($($pattern)|+ => (),)+
+                }
+            }
+        };
+    };
+}
+
+/// The outcome of `match_result_full!`: whether the pattern matched, and its bindings if it did.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct MatchResult<B> {
+    /// Whether the pattern matched.
+    pub matched: bool,
+    /// The tuple of bindings, if the pattern matched.
+    pub bindings: Option<B>,
+}
+
+/// Match an expression against a pattern, returning both whether it matched and the listed bindings.
+///
+/// Syntax: `match_result_full!(` *expression* `,` *pattern* [ `if` *guard* ] `=> {` *binding* `,` ... `})`
+///
+/// The macro evaluates to a [`MatchResult`] whose `bindings` are a tuple of the listed bindings, in order,
+/// e.g. `(x, y)`, or `(x,)` for a single one.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     let result = match_result_full!((3, "three"), (n, name) if n > 0 => { n, name });
+///     assert!(result.matched);
+///     assert_eq!(result.bindings, Some((3, "three")));
+/// }
+/// ```
+#[macro_export]
+macro_rules! match_result_full {
+    ($expression:expr, $($pattern:pat)|+ $(if $ifguard:expr)? => { $($binding:ident),* $(,)? }) => {
+        match $expression {
+            $($pattern)|+ $(if $ifguard)? => $crate::MatchResult { matched: true, bindings: Some(($($binding,)*)) },
+            #[allow(unreachable_patterns)]
+            _ => $crate::MatchResult { matched: false, bindings: None }
+        }
+    };
+}
+
+/// Assert that the items of an iterable match a sequence of patterns, some of which may repeat.
+///
+/// Syntax: `assert_sequence_matches!(` *iterable* `, [` *step* `,` ... `])`
+///
+/// Each step is either a *pattern* [ `if` *guard* ], which matches exactly one item,
+/// or `repeat(` *pattern* [ `if` *guard* ] `)`, which matches zero or more consecutive items.
+/// Every item must be matched. Like a regular expression, a repeated step gives up items to the following steps
+/// if needed. The panic message reports the index and the value of the first item that cannot be matched,
+/// or that the items ended early. At most 63 steps are supported, which is checked at compile time.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// #[derive(Debug)]
+/// enum Frame {
+///     Start,
+///     Data(u8),
+///     End,
+/// }
+///
+/// fn main() {
+///     let log = vec![Frame::Start, Frame::Data(1), Frame::Data(2), Frame::End];
+///     assert_sequence_matches!(log, [Frame::Start, repeat(Frame::Data(_)), Frame::End]);
+/// }
+/// ```
+///
+/// ```compile_fail
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     assert_sequence_matches!(vec![0; 64], [
+///         _, _, _, _, _, _, _, _, _, _, _, _, _, _, _, _,
+///         _, _, _, _, _, _, _, _, _, _, _, _, _, _, _, _,
+///         _, _, _, _, _, _, _, _, _, _, _, _, _, _, _, _,
+///         _, _, _, _, _, _, _, _, _, _, _, _, _, _, _, _,
+///     ]);
+/// }
+/// ```
+#[macro_export]
+macro_rules! assert_sequence_matches {
+    (@steps [$($steps:tt)*]) => {
+        [$($steps)*]
+    };
+    (@steps [$($steps:tt)*] repeat($($pattern:pat)|+ $(if $ifguard:expr)?) $(, $($rest:tt)*)?) => {
+        $crate::assert_sequence_matches!(
+            @steps [$($steps)* (true, &|item| $crate::matches!(#[allow(unreachable_patterns)] *item, $($pattern)|+ $(if $ifguard)?)),]
+            $($($rest)*)?
+        )
+    };
+    (@steps [$($steps:tt)*] $($pattern:pat)|+ $(if $ifguard:expr)? $(, $($rest:tt)*)?) => {
+        $crate::assert_sequence_matches!(
+            @steps [$($steps)* (false, &|item| $crate::matches!(#[allow(unreachable_patterns)] *item, $($pattern)|+ $(if $ifguard)?)),]
+            $($($rest)*)?
+        )
+    };
+    (@count [$($count:tt)*]) => {
+        0 $($count)*
+    };
+    (@count [$($count:tt)*] repeat($($pattern:pat)|+ $(if $ifguard:expr)?) $(, $($rest:tt)*)?) => {
+        $crate::assert_sequence_matches!(@count [$($count)* + 1] $($($rest)*)?)
+    };
+    (@count [$($count:tt)*] $($pattern:pat)|+ $(if $ifguard:expr)? $(, $($rest:tt)*)?) => {
+        $crate::assert_sequence_matches!(@count [$($count)* + 1] $($($rest)*)?)
+    };
+    ($iterable:expr, [$($steps:tt)*]) => {{
+        const _: () = assert!(
+            $crate::assert_sequence_matches!(@count [] $($steps)*) < 64,
+            "assert_sequence_matches! supports at most 63 steps"
+        );
+        match $crate::__private::match_sequence(
+            $crate::__private::IntoIterator::into_iter($iterable),
+            &$crate::assert_sequence_matches!(@steps [] $($steps)*),
+        ) {
+            $crate::__private::Result::Ok(()) => (),
+            $crate::__private::Result::Err((index, $crate::__private::Option::Some(ref item))) => panic!(
+                "assertion failed: element {} (`{}`) does not continue the sequence `[{}]`",
+                index,
+                $crate::__private::Value(item),
+                stringify!($($steps)*)
+            ),
+            $crate::__private::Result::Err((count, $crate::__private::Option::None)) => panic!(
+                "assertion failed: sequence ended after {} elements without matching `[{}]`",
+                count,
+                stringify!($($steps)*)
+            ),
+        }
+    }};
+}
+
+/// Returns `Result::Ok` with the result if the pattern matches, or `Result::Err` with the reason it did not.
+///
+/// Syntax: `option_match_reason!(` *expression* `,` *pattern* [ `if` *guard* ] `=>` *result* `)`
+///
+/// The reason is the stringified pattern, as with `try_unwrap_match!`, which this macro expands to.
+/// It is meant for `filter_map` pipelines like `option_match!`, where the skipped items should be logged.
+///
+/// # Examples
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     let mut skipped = Vec::new();
+///     let ports: Vec<u16> = ["80", "http", "443"]
+///         .iter()
+///         .filter_map(|s| match option_match_reason!(s.parse::<u16>(), Ok(port) => port) {
+///             Ok(port) => Some(port),
+///             Err(reason) => {
+///                 skipped.push(format!("{}: expected `{}`", s, reason));
+///                 None
+///             }
+///         })
+///         .collect();
+///     assert_eq!(ports, [80, 443]);
+///     assert_eq!(skipped, ["http: expected `Ok(port)`"]);
+/// }
+/// ```
+#[macro_export]
+macro_rules! option_match_reason {
+    ($expression:expr, $($pattern:pat)|+ $(if $ifguard:expr)? => $result:expr) => {
+        $crate::try_unwrap_match!($expression, $($pattern)|+ $(if $ifguard)? => $result)
+    };
+}
+
+/// Assert that a value matches a refutable pattern both before and after a mutation.
+///
+/// Syntax: `assert_preserves_match!(&mut` *place* `,` *pattern* [ `if` *guard* ] `,` *mutation* `)`
+///
+/// *mutation* is called with a `&mut` to the place, like the action of `assert_transition!`.
+/// The panic message says whether the value did not match "before" or "after the mutation".
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// #[derive(Debug)]
+/// enum Balance {
+///     Credit(u32),
+///     Debt(u32),
+/// }
+///
+/// fn main() {
+///     let mut balance = Balance::Credit(10);
+///     assert_preserves_match!(&mut balance, Balance::Credit(_), |b: &mut Balance| {
+///         if let Balance::Credit(ref mut amount) = *b {
+///             *amount += 5;
+///         }
+///     });
+/// }
+/// ```
+#[macro_export]
+macro_rules! assert_preserves_match {
+    (&mut $place:expr, $($pattern:pat)|+ $(if $ifguard:expr)?, $mutation:expr $(,)?) => {{
+        let place = &mut $place;
+        match *place {
+            $($pattern)|+ $(if $ifguard)? => (),
+            #[allow(unreachable_patterns)]
+            ref value => panic!(
+                "assertion failed: `{}` does not match `{}` before the mutation",
+                $crate::__private::Value(value),
+                stringify!($($pattern)|+ $(if $ifguard)?)
+            )
+        }
+        ($mutation)(&mut *place);
+        match *place {
+            $($pattern)|+ $(if $ifguard)? => (),
+            #[allow(unreachable_patterns)]
+            ref value => panic!(
+                "assertion failed: `{}` does not match `{}` after the mutation",
+                $crate::__private::Value(value),
+                stringify!($($pattern)|+ $(if $ifguard)?)
+            )
+        }
+    }};
+}
+
+/// Returns the `mem::discriminant` of an expression, borrowed, for comparing variants in guards.
+///
+/// Syntax: `variant_of!(` *expression* `)`
+///
+/// # Examples
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// enum Token {
+///     Number(u32),
+///     Word(&'static str),
+/// }
+///
+/// fn main() {
+///     let reference = Token::Number(0);
+///     let token = Token::Number(42);
+///     assert!(matches!(token, _ if variant_of!(token) == variant_of!(reference)));
+///     assert!(variant_of!(Token::Word("a")) != variant_of!(reference));
+/// }
+/// ```
+#[macro_export]
+macro_rules! variant_of {
+    ($expression:expr $(,)?) => {
+        $crate::__private::discriminant(&$expression)
+    };
+}
+
+/// Unwrap a pattern, or return `Option::None` from the enclosing function.
+///
+/// Syntax: `extract_opt!(` *expression* `,` *pattern* [ `if` *guard* ] `=>` *result* `)`
+///
+/// This is the same as `question_match!`, named to pair with `extract_res!`.
+///
+/// # Examples
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn port(address: &str) -> Option<u16> {
+///     let port = extract_opt!(address.rsplit_once(':'), Some((_, port)) => port);
+///     port.parse().ok()
+/// }
+///
+/// fn main() {
+///     assert_eq!(port("localhost:8080"), Some(8080));
+///     assert_eq!(port("localhost"), None);
+/// }
+/// ```
+#[macro_export]
+macro_rules! extract_opt {
+    ($expression:expr, $($pattern:pat)|+ $(if $ifguard:expr)? => $result:expr) => {
+        $crate::question_match!($expression, $($pattern)|+ $(if $ifguard)? => $result)
+    };
+}
+
+/// Unwrap a pattern, or return `Result::Err` with the given error from the enclosing function.
+///
+/// Syntax: `extract_res!(` *expression* `,` *pattern* [ `if` *guard* ] `=>` *result* `,` *error* `)`
+///
+/// Like the `?` operator, the error is converted with `From::from`. It is only evaluated if the pattern does not match.
+///
+/// # Examples
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn port(address: &str) -> Result<u16, String> {
+///     let port = extract_res!(address.rsplit_once(':'), Some((_, port)) => port, format!("no port in `{}`", address));
+///     port.parse().map_err(|_| format!("bad port `{}`", port))
+/// }
+///
+/// fn main() {
+///     assert_eq!(port("localhost:8080"), Ok(8080));
+///     assert_eq!(port("localhost"), Err("no port in `localhost`".to_string()));
+/// }
+/// ```
+#[macro_export]
+macro_rules! extract_res {
+    ($expression:expr, $($pattern:pat)|+ $(if $ifguard:expr)? => $result:expr, $error:expr $(,)?) => {
+        match $expression {
+            $($pattern)|+ $(if $ifguard)? => $result,
+            #[allow(unreachable_patterns)]
+            _ => return $crate::__private::Result::Err($crate::__private::From::from($error))
+        }
+    };
+}
+
+/// Assert that an expression matches a refutable pattern, and that a binding of the pattern equals a value.
+///
+/// Syntax: `assert_matches_eq!(` *expression* `,` *pattern* [ `if` *guard* ] `=>` *binding* `,` *expected* `)`
+///
+/// If the pattern does not match, this panics like `assert_matches!`. If it matches but the binding is not equal
+/// to *expected*, the panic message shows both values, like `assert_eq!`.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// #[derive(Debug)]
+/// enum Response {
+///     Ok { body: String },
+///     NotFound,
+/// }
+///
+/// fn main() {
+///     let response = Response::Ok { body: "hello".to_string() };
+///     assert_matches_eq!(response, Response::Ok { body } => body, "hello");
+/// }
+/// ```
+#[macro_export]
+macro_rules! assert_matches_eq {
+    ($expression:expr, $($pattern:pat)|+ $(if $ifguard:expr)? => $binding:ident, $expected:expr $(,)?) => {
+        match $expression {
+            $($pattern)|+ $(if $ifguard)? => match (&$binding, &$expected) {
+                (left, right) => {
+                    if !(*left == *right) {
+                        panic!(
+                            "assertion failed: `{}` matched, but `{} == {}` failed\n  left: {:?}\n right: {:?}",
+                            stringify!($($pattern)|+ $(if $ifguard)?),
+                            stringify!($binding),
+                            stringify!($expected),
+                            left,
+                            right
+                        )
+                    }
+                }
+            },
+            #[allow(unreachable_patterns)]
+            ref value => panic!("{}", $crate::__private::Mismatch(stringify!($expression), value, stringify!($($pattern)|+ $(if $ifguard)?)))
+        }
+    };
+}
+
+/// Assert that an expression matches a refutable pattern on every `n`-th call, using debug assertions.
+///
+/// Syntax: `sample_assert_matches!(` *expression* `,` *pattern* [ `if` *guard* ] `,` *every* `)`
+///
+/// Each invocation of the macro has its own atomic counter, and only the first of every *every* calls
+/// evaluates the expression and checks it like `debug_assert_matches!`; the other calls do nothing.
+/// *every* must not be 0, which panics on the first call. Without debug assertions, the macro expands to nothing.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     let data: Vec<Option<u32>> = (0..1000).map(Some).collect();
+///     for item in &data {
+///         sample_assert_matches!(item, Some(_), 100);
+///     }
+/// }
+/// ```
+#[cfg(debug_assertions)]
+#[macro_export]
+macro_rules! sample_assert_matches {
+    ($expression:expr, $($pattern:pat)|+ $(if $ifguard:expr)?, $every:expr $(,)?) => {{
+        static CALLS: $crate::__private::AtomicUsize = $crate::__private::AtomicUsize::new(0);
+        let every: usize = $every;
+        assert!(every > 0, "sample_assert_matches! needs `every` to be at least 1, got {}", every);
+        if CALLS.fetch_add(1, $crate::__private::Ordering::Relaxed) % every == 0 {
+            $crate::debug_assert_matches!($expression, $($pattern)|+ $(if $ifguard)?);
+        }
+    }};
+}
+
+/// Assert that an expression matches a refutable pattern on every `n`-th call, using debug assertions.
+#[cfg(not(debug_assertions))]
+#[macro_export]
+macro_rules! sample_assert_matches {
+    ($expression:expr, $($pattern:pat)|+ $(if $ifguard:expr)?, $every:expr $(,)?) => {};
+}
+
+/// Assert that the items of an iterable cover every variant in a list.
+///
+/// Syntax: `assert_covers_variants!(` *iterable* `, [` *variant* `,` ... `])`
+///
+/// Each *variant* is the path of a unit, tuple or struct variant, as with `assert_variant!`.
+/// The items are consumed, and the panic message lists the variants that never appeared.
+/// Items of other variants are ignored; combine with `assert_patterns_exhaustive!` to make sure the list is complete.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// enum Event {
+///     Open,
+///     Data(u8),
+///     Close { clean: bool },
+/// }
+///
+/// fn main() {
+///     let events = vec![Event::Open, Event::Data(1), Event::Data(2), Event::Close { clean: true }];
+///     assert_covers_variants!(events, [Event::Open, Event::Data, Event::Close]);
+/// }
+/// ```
+#[macro_export]
+macro_rules! assert_covers_variants {
+    ($iterable:expr, [$($variant:path),+ $(,)?]) => {{
+        let mut checks = [$((stringify!($variant), false)),+];
+        for item in $iterable {
+            let matched = [$($crate::matches!(item, $variant { .. })),+];
+            for (check, matched) in checks.iter_mut().zip(matched.iter()) {
+                check.1 |= *matched;
+            }
+        }
+        if checks.iter().any(|check| !check.1) {
+            panic!("assertion failed: variants never seen: {}", $crate::__private::Failing(&checks));
+        }
+    }};
+}
+
+/// Unwrap a `Result` and a pattern on its `Ok` value.
+///
+/// Syntax: `unwrap_ok_matches!(` *result* `,` *pattern* [ `if` *guard* ] `=>` *expression* `)`
+///
+/// The macro evaluates to *expression* if *result* is `Ok` and its value matches the pattern.
+/// Otherwise, it panics with a message that shows either the error or the `Ok` value that did not match.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     let parsed: Result<(u8, u8), String> = Ok((1, 2));
+///     let minor = unwrap_ok_matches!(parsed, (1, minor) => minor);
+///     assert_eq!(minor, 2);
+/// }
+/// ```
+#[macro_export]
+macro_rules! unwrap_ok_matches {
+    ($result:expr, $($pattern:pat)|+ $(if $ifguard:expr)? => $expression:expr) => {
+        match $result {
+            $crate::__private::Result::Ok($($pattern)|+) $(if $ifguard)? => $expression,
+            #[allow(unreachable_patterns)]
+            $crate::__private::Result::Ok(ref value) => panic!(
+                "assertion failed: `Ok` value `{}` does not match `{}`",
+                $crate::__private::Value(value),
+                stringify!($($pattern)|+ $(if $ifguard)?)
+            ),
+            $crate::__private::Result::Err(ref error) => panic!(
+                "assertion failed: expected `Ok` matching `{}`, got `Err({})`",
+                stringify!($($pattern)|+ $(if $ifguard)?),
+                $crate::__private::Value(error)
+            ),
+        }
+    };
+}
+
+/// Match an expression against known arms, passing any other value to a fallback.
+///
+/// Syntax: `match_or_log!(` *expression* `, {` *pattern* [ `if` *guard* ] `=>` *result* `,` ... `}, |` *unknown* `|` *fallback* `)`
+///
+/// This expands to a `match` with the given arms and a last arm that binds the whole value to *unknown*
+/// and evaluates to *fallback*, e.g. to log it and return a default.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     let mut unknown = Vec::new();
+///     for code in [200, 404, 999] {
+///         let text = match_or_log!(code, {
+///             200 => "ok",
+///             404 => "not found",
+///         }, |other| {
+///             unknown.push(other);
+///             "unknown"
+///         });
+///         println!("{}", text);
+///     }
+///     assert_eq!(unknown, [999]);
+/// }
+/// ```
+#[macro_export]
+macro_rules! match_or_log {
+    ($expression:expr, { $($($pattern:pat)|+ $(if $ifguard:expr)? => $result:expr),+ $(,)? }, |$unknown:ident| $fallback:expr) => {
+        match $expression {
+            $($($pattern)|+ $(if $ifguard)? => $result,)+
+            #[allow(unreachable_patterns)]
+            $unknown => $fallback
+        }
+    };
+}
+
+/// Check if a byte slice starts with a byte-string or string literal.
+///
+/// Syntax: `bytes_match!(` *bytes* `,` *literal* `)`
+///
+/// Byte-string literals such as `b"GET"` cannot be used as slice patterns in `matches!`, so this compares bytes instead.
+/// *bytes* may be a byte slice, array or `Vec`, or a string, which is compared by its bytes. Escapes in the literal,
+/// such as `b"\r\n"`, are handled by the compiler. See `bytes_eq_match!` for an exact comparison.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     let request: &[u8] = b"GET / HTTP/1.1\r\n";
+///     assert!(bytes_match!(request, b"GET "));
+///     assert!(!bytes_match!(request, "POST "));
+/// }
+/// ```
+#[macro_export]
+macro_rules! bytes_match {
+    ($bytes:expr, $literal:literal $(,)?) => {
+        $crate::__private::Elements::elements(&$bytes).starts_with($crate::__private::Elements::elements(&$literal))
+    };
+}
+
+/// Check if a byte slice is equal to a byte-string or string literal.
+///
+/// Syntax: `bytes_eq_match!(` *bytes* `,` *literal* `)`
+///
+/// Like `bytes_match!`, but the whole slice must be equal to the literal.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     let line: &[u8] = b"\r\n";
+///     assert!(bytes_eq_match!(line, b"\r\n"));
+///     assert!(!bytes_eq_match!(line, b"\r"));
+/// }
+/// ```
+#[macro_export]
+macro_rules! bytes_eq_match {
+    ($bytes:expr, $literal:literal $(,)?) => {
+        $crate::__private::Elements::elements(&$bytes) == $crate::__private::Elements::elements(&$literal)
+    };
+}
+
+/// Like `unwrap_match!`, but the result must be `Send`.
+///
+/// Syntax: `unwrap_match_send!(` *expression* `,` *pattern* `=>` *result* [ `,` *error message* ] `)`
+///
+/// This is useful for values extracted in async code that has to be `Send`: a result that is not `Send`
+/// is reported where it is extracted, rather than at a distant `spawn`.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// use std::sync::Arc;
+///
+/// fn main() {
+///     let shared = Some(Arc::new(5));
+///     let value = unwrap_match_send!(shared, Some(value) => value);
+///     std::thread::spawn(move || assert_eq!(*value, 5)).join().unwrap();
+/// }
+/// ```
+///
+/// A result that is not `Send` is a compile error:
+///
+/// ```compile_fail
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// use std::rc::Rc;
+///
+/// fn main() {
+///     let shared = Some(Rc::new(5));
+///     let value = unwrap_match_send!(shared, Some(value) => value);
+/// }
+/// ```
+#[macro_export]
+macro_rules! unwrap_match_send {
+    ($expression:expr, $($pattern:pat)|* $(if $ifguard:expr)? => $result:expr $(, $($msg:tt)+)?) => {
+        $crate::__private::assert_send($crate::unwrap_match!($expression, $($pattern)|* $(if $ifguard)? => $result $(, $($msg)+)?))
+    };
+}
+
+/// Collects the results for all nodes of a tree that match a pattern into a `Vec`.
+///
+/// Syntax: `collect_matches_recursive!(` *root* `,` *children* `,` *pattern* [ `if` *guard* ] `=>` *result* `)`
+///
+/// *children* is a closure returning the children of a node, as an iterable of the same type as *root*, which is
+/// usually a reference. The nodes are visited depth-first, each node before its children, without recursion.
+/// This macro requires the `std` feature.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// enum Expr {
+///     Number(i32),
+///     Add(Vec<Expr>),
+/// }
+///
+/// impl Expr {
+///     fn children(&self) -> &[Expr] {
+///         match self {
+///             Expr::Add(operands) => operands,
+///             Expr::Number(_) => &[],
+///         }
+///     }
+/// }
+///
+/// fn main() {
+///     let expr = Expr::Add(vec![Expr::Number(1), Expr::Add(vec![Expr::Number(2), Expr::Number(3)])]);
+///     let numbers = collect_matches_recursive!(&expr, |node| node.children(), Expr::Number(n) => *n);
+///     assert_eq!(numbers, [1, 2, 3]);
+/// }
+/// ```
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! collect_matches_recursive {
+    ($root:expr, $children:expr, $($pattern:pat)|+ $(if $ifguard:expr)? => $result:expr) => {{
+        let root = $root;
+        let mut children = $crate::__private::children(&root, $children);
+        let mut found = $crate::__private::Vec::new();
+        let mut stack = $crate::__private::Vec::new();
+        match root {
+            $($pattern)|+ $(if $ifguard)? => found.push($result),
+            #[allow(unreachable_patterns)]
+            _ => (),
+        }
+        stack.push($crate::__private::IntoIterator::into_iter(children(root)));
+        while let $crate::__private::Option::Some(siblings) = stack.last_mut() {
+            match siblings.next() {
+                $crate::__private::Option::Some(node) => {
+                    match node {
+                        $($pattern)|+ $(if $ifguard)? => found.push($result),
+                        #[allow(unreachable_patterns)]
+                        _ => (),
+                    }
+                    stack.push($crate::__private::IntoIterator::into_iter(children(node)));
+                }
+                $crate::__private::Option::None => {
+                    stack.pop();
+                }
+            }
+        }
+        found
+    }};
+}
+
+/// Assert that an expression matches none of a list of patterns.
+///
+/// Syntax: `assert_matches_complement!(` *expression* `, [` *pattern* [ `if` *guard* ] `,` ... `])`
+///
+/// The patterns are tried in order and may bind different names. The panic message reports the value
+/// and the first pattern that matched it.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     let port: Result<u16, String> = Ok(8080);
+///     assert_matches_complement!(port, [Err(_), Ok(0..=1023), Ok(p) if p == u16::MAX]);
+/// }
+/// ```
+#[macro_export]
+macro_rules! assert_matches_complement {
+    ($expression:expr, [$($($pattern:pat)|+ $(if $ifguard:expr)?),+ $(,)?]) => {
+        match $expression {
+            ref value => {
+                $(
+                    if $crate::matches!(*value, $($pattern)|+ $(if $ifguard)?) {
+                        panic!(
+                            "assertion failed: `{}` unexpectedly matched `{}`",
+                            $crate::__private::Value(value),
+                            stringify!($($pattern)|+ $(if $ifguard)?)
+                        )
+                    }
+                )+
+            }
+        }
+    };
+}
+
+/// Unwrap a pattern from a mutably borrowed place, returning mutable references into it.
+///
+/// Syntax: `unwrap_match_mut!(&mut` *place* `,` *pattern* [ `if` *guard* ] `=>` *result* [ `,` *error message* ] `)`
+///
+/// The place is borrowed mutably for as long as the result is used, so bindings, either `ref mut` ones or
+/// plain ones through the default binding mode, can be returned and mutated in place.
+/// Like `unwrap_match!`, this panics with a default message or the given one if the pattern does not match.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// #[derive(Debug)]
+/// enum Buffer {
+///     Empty,
+///     Bytes(Vec<u8>),
+/// }
+///
+/// fn bytes(buffer: &mut Buffer) -> &mut Vec<u8> {
+///     unwrap_match_mut!(&mut *buffer, Buffer::Bytes(ref mut bytes) => bytes)
+/// }
+///
+/// fn main() {
+///     let mut buffer = Buffer::Bytes(vec![1]);
+///     bytes(&mut buffer).push(2);
+///     assert_matches!(buffer, Buffer::Bytes(ref bytes) if bytes == &[1, 2]);
+/// }
+/// ```
+#[macro_export]
+macro_rules! unwrap_match_mut {
+    (&mut $place:expr, $($pattern:pat)|+ $(if $ifguard:expr)? => $result:expr $(, $($msg:tt)+)?) => {
+        $crate::unwrap_match!(&mut $place, $($pattern)|+ $(if $ifguard)? => $result $(, $($msg)+)?)
+    };
+}
+
+/// Returns `Option::Some` if the pattern matches and a fallible guard holds, or `Option::None` otherwise.
+///
+/// Syntax: `option_match_try!(` *expression* `,` *pattern* `,` *guard* `=>` *result* `)`
+///
+/// Like `option_match!` with an `if` guard, but the guard may use `?` on `Option`s, e.g. `s.parse().ok()? > 3`.
+/// A guard that fails with `?` is treated like a guard that evaluates to `false`, and the macro returns `None`.
+///
+/// # Examples
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     let settings = [("port", "8080"), ("port", "http"), ("host", "42")];
+///     let ports: Vec<&str> = settings
+///         .iter()
+///         .filter_map(|setting| option_match_try!(setting, ("port", value), value.parse::<u16>().ok()? >= 1024 => *value))
+///         .collect();
+///     assert_eq!(ports, ["8080"]);
+/// }
+/// ```
+#[macro_export]
+macro_rules! option_match_try {
+    ($expression:expr, $($pattern:pat)|+, $guard:expr => $result:expr) => {
+        match $expression {
+            $($pattern)|+ if $crate::__private::try_guard(|| $crate::__private::Option::Some($guard)) => {
+                $crate::__private::Option::Some($result)
+            }
+            #[allow(unreachable_patterns)]
+            _ => $crate::__private::Option::None
+        }
+    };
+}
+
+/// Returns `Option::Some` if a mutably borrowed slice matches a fixed-length slice pattern, or `Option::None` otherwise.
+///
+/// Syntax: `slice_match_mut!(&mut` *slice* `, [` *pattern* `,` ... `]` [ `if` *guard* ] `=>` *result* `)`
+///
+/// *slice* may be a slice, an array or a `Vec`. The element bindings are `&mut` references into it through the
+/// default binding mode, so *result* can return them for in-place edits of specific positions.
+/// The guard can only read them, e.g. `*a > *b`.
+///
+/// # Examples
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     let mut pair = vec![3, 1];
+///     if let Some((a, b)) = slice_match_mut!(&mut pair, [a, b] if *a > *b => (a, b)) {
+///         std::mem::swap(a, b);
+///     }
+///     assert_eq!(pair, [1, 3]);
+/// }
+/// ```
+#[macro_export]
+macro_rules! slice_match_mut {
+    (&mut $slice:expr, [$($element:pat),* $(,)?] $(if $ifguard:expr)? => $result:expr) => {
+        $crate::option_match!(&mut $slice[..], [$($element),*] $(if $ifguard)? => $result)
+    };
+}
+
+/// Returns `Result::Ok` if the pattern matches with the given value, or `Result::Err` with an error built from the pattern.
+///
+/// Syntax: `try_match_err!(` *expression* `,` *pattern* [ `if` *guard* ] `=>` *result* `,` *constructor* `)`
+///
+/// Like `try_unwrap_match!`, but the stringified pattern is passed as a `&'static str` to *constructor*,
+/// a closure or a function such as a tuple variant, so that the mismatch becomes a variant of the caller's own
+/// error type, e.g. one derived with `thiserror`.
+///
+/// # Examples
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// #[derive(Debug, PartialEq)]
+/// enum Error {
+///     UnexpectedFrame { pattern: &'static str },
+/// }
+///
+/// enum Frame {
+///     Data(u32),
+///     Close,
+/// }
+///
+/// fn payload(frame: Frame) -> Result<u32, Error> {
+///     try_match_err!(frame, Frame::Data(n) => n, |pattern| Error::UnexpectedFrame { pattern })
+/// }
+///
+/// fn main() {
+///     assert_eq!(payload(Frame::Data(2)), Ok(2));
+///     assert_eq!(payload(Frame::Close), Err(Error::UnexpectedFrame { pattern: "Frame::Data(n)" }));
+/// }
+/// ```
+#[macro_export]
+macro_rules! try_match_err {
+    ($expression:expr, $($pattern:pat)|+ $(if $ifguard:expr)? => $result:expr, $constructor:expr $(,)?) => {
+        match $expression {
+            $($pattern)|+ $(if $ifguard)? => $crate::__private::Result::Ok($result),
+            #[allow(unreachable_patterns)]
+            _ => $crate::__private::Result::Err(($constructor)(stringify!($($pattern)|+ $(if $ifguard)?)))
+        }
+    };
+}
+
+/// Assert that the first `n` elements of an iterable match a pattern, and that no later element matches another one.
+///
+/// Syntax: `assert_prefix_then_none!(` *iterable* `,` *n* `,` *prefix pattern* [ `if` *guard* ] `,`
+/// *forbidden pattern* [ `if` *guard* ] `)`
+///
+/// This checks streams that start with a handshake and then must not repeat it. The panic message reports
+/// the index and the value of the first element that violates either pattern, or how many elements there were
+/// if the iterable ends before the prefix does.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// #[derive(Debug)]
+/// enum Message {
+///     Hello(u8),
+///     Data(Vec<u8>),
+/// }
+///
+/// fn main() {
+///     let stream = vec![Message::Hello(1), Message::Hello(2), Message::Data(vec![]), Message::Data(vec![3])];
+///     assert_prefix_then_none!(stream, 2, Message::Hello(_), Message::Hello(_));
+/// }
+/// ```
+#[macro_export]
+macro_rules! assert_prefix_then_none {
+    ($iterable:expr, $n:expr, $($prefix:pat)|+ $(if $prefixguard:expr)?, $($forbidden:pat)|+ $(if $forbiddenguard:expr)?) => {{
+        let n: usize = $n;
+        let mut count = 0usize;
+        for (index, ref item) in $crate::__private::IntoIterator::into_iter($iterable).enumerate() {
+            count += 1;
+            if index < n {
+                if !$crate::matches!(#[allow(unreachable_patterns)] *item, $($prefix)|+ $(if $prefixguard)?) {
+                    panic!(
+                        "assertion failed: element {} (`{}`) of the prefix does not match `{}`",
+                        index,
+                        $crate::__private::Value(item),
+                        stringify!($($prefix)|+ $(if $prefixguard)?)
+                    )
+                }
+            } else if $crate::matches!(#[allow(unreachable_patterns)] *item, $($forbidden)|+ $(if $forbiddenguard)?) {
+                panic!(
+                    "assertion failed: element {} (`{}`) after the prefix unexpectedly matched `{}`",
+                    index,
+                    $crate::__private::Value(item),
+                    stringify!($($forbidden)|+ $(if $forbiddenguard)?)
+                )
+            }
+        }
+        if count < n {
+            panic!(
+                "assertion failed: expected a prefix of {} elements matching `{}`, but there were only {}",
+                n,
+                stringify!($($prefix)|+ $(if $prefixguard)?),
+                count
+            )
+        }
+    }};
+}
+
+/// Check if an expression matches a refutable pattern, logging a `defmt` warning with the pattern if it does not.
+///
+/// Syntax: `defmt_warn_matches!(` *expression* `,` *pattern* [ `if` *guard* ] `)`
+///
+/// Returns the same boolean as `matches!`. `defmt` cannot format arbitrary `Debug` values, so only the
+/// stringified pattern is logged, with `defmt::warn!`; as with any use of `defmt`, the calling crate must
+/// depend on `defmt` and provide a global logger.
+/// This works without `std`, and the macro requires the `defmt` feature.
+///
+/// # Examples
+///
+#[cfg_attr(feature = "defmt", doc = "```")]
+#[cfg_attr(not(feature = "defmt"), doc = "```ignore")]
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// # #[defmt::global_logger]
+/// # struct Logger;
+/// #
+/// # unsafe impl defmt::Logger for Logger {
+/// #     fn acquire() {}
+/// #     unsafe fn flush() {}
+/// #     unsafe fn release() {}
+/// #     unsafe fn write(_bytes: &[u8]) {}
+/// # }
+/// #
+/// # defmt::timestamp!("");
+/// #
+/// fn on_frame(header: u8) {
+///     if defmt_warn_matches!(header, 0x80..=0x8F) {
+///         // handle the frame
+///     }
+/// }
+///
+/// fn main() {
+///     on_frame(0x81);
+/// }
+/// ```
+#[cfg(feature = "defmt")]
+#[macro_export]
+macro_rules! defmt_warn_matches {
+    ($expression:expr, $($pattern:pat)|+ $(if $ifguard:expr)?) => {{
+        let matched = $crate::matches!($expression, $($pattern)|+ $(if $ifguard)?);
+        if !matched {
+            $crate::__private::defmt::warn!("value does not match `{=str}`", stringify!($($pattern)|+ $(if $ifguard)?));
+        }
+        matched
+    }};
+}
+
+/// Assert that a value, encoded and then decoded, matches a refutable pattern.
+///
+/// Syntax: `assert_roundtrip_matches!(` *value* `,` *encode* `,` *decode* `,` *pattern* [ `if` *guard* ] `)`
+///
+/// *encode* is called with *value*, and *decode* with a reference to the encoded form, which is kept so that
+/// the panic message can show it next to the decoded value. Both must implement `Debug`.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn encode(n: u16) -> [u8; 2] {
+///     n.to_be_bytes()
+/// }
+///
+/// fn decode(bytes: &[u8; 2]) -> Result<u16, ()> {
+///     Ok(u16::from_be_bytes(*bytes))
+/// }
+///
+/// fn main() {
+///     assert_roundtrip_matches!(513, encode, decode, Ok(513));
+/// }
+/// ```
+#[macro_export]
+macro_rules! assert_roundtrip_matches {
+    ($value:expr, $encode:expr, $decode:expr, $($pattern:pat)|+ $(if $ifguard:expr)?) => {
+        match ($encode)($value) {
+            encoded => match ($decode)(&encoded) {
+                $($pattern)|+ $(if $ifguard)? => (),
+                #[allow(unreachable_patterns)]
+                ref decoded => panic!(
+                    "assertion failed: round trip of `{}` does not match `{}`\n decoded: {:?}\n encoded: {:?}",
+                    stringify!($value),
+                    stringify!($($pattern)|+ $(if $ifguard)?),
+                    decoded,
+                    encoded
+                ),
+            },
+        }
+    };
+}
+
+/// Generate `TryFrom` implementations extracting the values of single-field tuple variants of an enum.
+///
+/// Syntax: `impl_try_into_variants!(` *enum* `, {` *variant* `=>` *type* `,` ... `})`
+///
+/// For each variant, `TryFrom<`*enum*`>` is implemented for *type*, returning the field of `enum::variant(field)`,
+/// or the original value as the error if it is a different variant. `TryInto` is then implemented through `TryFrom`.
+/// Each type can only be listed once, since it can only implement `TryFrom<`*enum*`>` once.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// use std::convert::TryFrom;
+///
+/// #[derive(Debug, PartialEq)]
+/// pub enum Value {
+///     Int(i64),
+///     Text(String),
+/// }
+///
+/// impl_try_into_variants!(Value, {
+///     Int => i64,
+///     Text => String,
+/// });
+///
+/// fn main() {
+///     assert_eq!(i64::try_from(Value::Int(3)), Ok(3));
+///     assert_eq!(String::try_from(Value::Int(3)), Err(Value::Int(3)));
+/// }
+/// ```
+#[macro_export]
+macro_rules! impl_try_into_variants {
+    ($enum:ident, { $($variant:ident => $type:ty),* $(,)? }) => {
+        $(
+            impl $crate::__private::TryFrom<$enum> for $type {
+                type Error = $enum;
+
+                fn try_from(value: $enum) -> $crate::__private::Result<$type, $enum> {
+                    match value {
+                        $enum::$variant(inner) => $crate::__private::Result::Ok(inner),
+                        #[allow(unreachable_patterns)]
+                        other => $crate::__private::Result::Err(other),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+/// Assert that the elements of an iterable match a list of patterns in any order, each pattern matching one element.
+///
+/// Syntax: `assert_matches_unordered!(` *iterable* `, [` *pattern* [ `if` *guard* ] `,` ... `])`
+///
+/// The patterns may overlap: the assertion only fails if there is no way to pair every element with a different
+/// pattern that it matches. The panic message then lists the patterns and the elements left over by the best
+/// pairing. This macro supports at most 64 patterns, which is checked at compile time, and requires the `std` feature.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// #[derive(Debug)]
+/// enum Event {
+///     Connected(u32),
+///     Message(String),
+/// }
+///
+/// fn main() {
+///     let events = vec![Event::Message("hi".into()), Event::Connected(2), Event::Connected(1)];
+///     assert_matches_unordered!(events, [Event::Connected(1), Event::Connected(_), Event::Message(_)]);
+/// }
+/// ```
+///
+/// ```compile_fail
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     assert_matches_unordered!(vec![0; 65], [
+///         _, _, _, _, _, _, _, _, _, _, _, _, _, _, _, _,
+///         _, _, _, _, _, _, _, _, _, _, _, _, _, _, _, _,
+///         _, _, _, _, _, _, _, _, _, _, _, _, _, _, _, _,
+///         _, _, _, _, _, _, _, _, _, _, _, _, _, _, _, _,
+///         _,
+///     ]);
+/// }
+/// ```
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! assert_matches_unordered {
+    ($iterable:expr, [$($($pattern:pat)|+ $(if $ifguard:expr)?),+ $(,)?]) => {{
+        const _: () = assert!(
+            [$(stringify!($($pattern)|+ $(if $ifguard)?)),+].len() <= 64,
+            "assert_matches_unordered! supports at most 64 patterns"
+        );
+        let patterns: &[&'static str] = &[$(stringify!($($pattern)|+ $(if $ifguard)?)),+];
+        let items: $crate::__private::Vec<_> = $crate::__private::IntoIterator::into_iter($iterable).collect();
+        let masks: $crate::__private::Vec<u64> = items
+            .iter()
+            .map(|item| {
+                [$($crate::matches!(#[allow(unreachable_patterns)] *item, $($pattern)|+ $(if $ifguard)?)),+]
+                    .iter()
+                    .enumerate()
+                    .fold(0, |mask, (pattern, &matched)| mask | (matched as u64) << pattern)
+            })
+            .collect();
+        let owners = $crate::__private::match_unordered(&masks, patterns.len());
+        if items.len() != patterns.len() || owners.contains(&$crate::__private::Option::None) {
+            panic!(
+                "assertion failed: `{}` does not match `[{}]` in any order{}",
+                stringify!($iterable),
+                stringify!($($($pattern)|+ $(if $ifguard)?),+),
+                $crate::__private::Unordered(&items, patterns, &owners)
+            )
+        }
+    }};
+}
+
+/// Assert that an expression matches a refutable pattern, with a backtrace of the mismatch in the panic message.
+///
+/// Syntax: `assert_matches_bt!(` *expression* `,` *pattern* [ `if` *guard* ] `)`
+///
+/// On a mismatch, a `std::backtrace::Backtrace` is captured and appended to the default message of `assert_matches!`.
+/// Like `Backtrace::capture`, this respects the `RUST_BACKTRACE` and `RUST_LIB_BACKTRACE` environment variables,
+/// and nothing is appended if backtraces are disabled. This macro requires the `backtrace` feature.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     assert_matches_bt!("42".parse::<u8>(), Ok(42));
+/// }
+/// ```
+#[cfg(feature = "backtrace")]
+#[macro_export]
+macro_rules! assert_matches_bt {
+    ($expression:expr, $($pattern:pat)|+ $(if $ifguard:expr)?) => {
+        match $expression {
+            $($pattern)|+ $(if $ifguard)? => (),
+            #[allow(unreachable_patterns)]
+            ref value => panic!(
+                "{}{}",
+                $crate::__private::Mismatch(stringify!($expression), value, stringify!($($pattern)|+ $(if $ifguard)?)),
+                $crate::__private::Trace(&$crate::__private::Backtrace::capture())
+            )
+        }
+    };
+}
+
+/// Matches a value against several independent patterns, returning a tuple with an `Option` for each of them.
+///
+/// Syntax: `multi_match!(` *expression* `, {` *pattern* [ `if` *guard* ] `=>` *result* `,` ... `})`
+///
+/// The expression is evaluated once and borrowed, and each pattern is matched against the same reference,
+/// so the bindings are references through the default binding mode. Unlike the arms of a `match`,
+/// every pattern is tried, and the *n*-th element of the tuple is `Some(`*result*`)` if the *n*-th pattern matched.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// enum Header {
+///     Host(String),
+///     ContentLength(i32),
+///     Other,
+/// }
+///
+/// fn main() {
+///     let header = Header::Host("example.com".into());
+///     let (host, length, org) = multi_match!(header, {
+///         Header::Host(name) => name.as_str(),
+///         Header::ContentLength(n) => *n,
+///         Header::Host(name) if name.ends_with(".org") => true,
+///     });
+///     assert_eq!((host, length, org), (Some("example.com"), None, None));
+/// }
+/// ```
+#[macro_export]
+macro_rules! multi_match {
+    ($expression:expr, { $($($pattern:pat)|+ $(if $ifguard:expr)? => $result:expr),+ $(,)? }) => {
+        match &$expression {
+            value => ($(
+                match value {
+                    $($pattern)|+ $(if $ifguard)? => $crate::__private::Option::Some($result),
+                    #[allow(unreachable_patterns)]
+                    _ => $crate::__private::Option::None,
+                },
+            )+)
+        }
+    };
+}
+
+/// Assert that a function returns a value matching a refutable pattern for each row of a table of inputs.
+///
+/// Syntax: `assert_matches_table!(` *function* `, [(` *input* `,` *pattern* [ `if` *guard* ] `),` ... `])`
+///
+/// *function* is evaluated once and called with each *input* in order. The panic message reports the index
+/// of the first failing row, its input expression, the returned value and the pattern.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     assert_matches_table!(|s: &str| s.parse::<u8>(), [
+///         ("7", Ok(7)),
+///         ("300", Err(_)),
+///         ("12", Ok(n) if n > 9),
+///     ]);
+/// }
+/// ```
+#[macro_export]
+macro_rules! assert_matches_table {
+    ($function:expr, [$(($input:expr, $($pattern:pat)|+ $(if $ifguard:expr)?)),+ $(,)?]) => {{
+        let function = $function;
+        let mut row = 0usize;
+        $(
+            match function($input) {
+                $($pattern)|+ $(if $ifguard)? => (),
+                #[allow(unreachable_patterns)]
+                ref value => panic!(
+                    "assertion failed: row {} (`{}`): `{}` does not match `{}`",
+                    row,
+                    stringify!($input),
+                    $crate::__private::Value(value),
+                    stringify!($($pattern)|+ $(if $ifguard)?)
+                ),
+            }
+            row += 1;
+        )+
+        let _ = row;
+    }};
+}
+
+/// Assert that a `Result` is `Ok`, returning the value inside it.
+///
+/// Syntax: `assert_ok!(` *expression* [ `,` *error message* ] `)`
+///
+/// If the expression is `Err`, this macro panics with the given error message or a default one
+/// that contains the `Debug` value of the error.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     let port: u16 = assert_ok!("8080".parse());
+///     assert_eq!(port, 8080);
+/// }
+/// ```
+#[macro_export]
+macro_rules! assert_ok {
+    ($expression:expr $(,)?) => {
+        match $expression {
+            $crate::__private::Result::Ok(value) => value,
+            $crate::__private::Result::Err(ref error) => panic!(
+                "assertion failed: expected `Ok`, got `Err({})`",
+                $crate::__private::Value(error)
+            ),
+        }
+    };
+    ($expression:expr, $($msg:tt)+) => {
+        match $expression {
+            $crate::__private::Result::Ok(value) => value,
+            $crate::__private::Result::Err(_) => panic!($($msg)+),
+        }
+    };
+}
+
+/// Assert that a `Result` is `Err`, returning the error inside it.
+///
+/// Syntax: `assert_err!(` *expression* [ `,` *error message* ] `)`
+///
+/// If the expression is `Ok`, this macro panics with the given error message or a default one
+/// that contains the `Debug` value inside the `Ok`.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     let error = assert_err!("http".parse::<u16>());
+///     assert_eq!(error.to_string(), "invalid digit found in string");
+/// }
+/// ```
+#[macro_export]
+macro_rules! assert_err {
+    ($expression:expr $(,)?) => {
+        match $expression {
+            $crate::__private::Result::Err(error) => error,
+            $crate::__private::Result::Ok(ref value) => panic!(
+                "assertion failed: expected `Err`, got `Ok({})`",
+                $crate::__private::Value(value)
+            ),
+        }
+    };
+    ($expression:expr, $($msg:tt)+) => {
+        match $expression {
+            $crate::__private::Result::Err(error) => error,
+            $crate::__private::Result::Ok(_) => panic!($($msg)+),
+        }
+    };
+}
+
+/// Check if an expression matches a refutable pattern with a guard that can mutate some state.
+///
+/// Syntax: `matches_with!(&mut` *state* `,` *expression* `,` *pattern* [ `if` *guard* ] `)`
+///
+/// *state* is a local variable, which the guard sees as a `&mut` under the same name, e.g. to push the values
+/// it accepts to a `Vec`. Returns the same boolean as `matches!`: the expression is evaluated once, and the guard,
+/// with its side effects, only runs if the pattern itself matched.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     let mut seen = Vec::new();
+///     let small = ["3", "x", "40", "5"]
+///         .iter()
+///         .filter(|s| matches_with!(&mut seen, s.parse::<u8>(), Ok(n) if { seen.push(n); n < 10 }))
+///         .count();
+///     assert_eq!(small, 2);
+///     assert_eq!(seen, [3, 40, 5]);
+/// }
+/// ```
+#[macro_export]
+macro_rules! matches_with {
+    (&mut $state:ident, $expression:expr, $($pattern:pat)|+ $(if $ifguard:expr)?) => {{
+        #[allow(unused_variables)]
+        let $state = &mut $state;
+        $crate::matches!($expression, $($pattern)|+ $(if $ifguard)?)
+    }};
+}
+
+/// Returns the value of an expression if it matches none of a list of forbidden patterns, or panics otherwise.
+///
+/// Syntax: `reject_match!(` *expression* `, [` *pattern* [ `if` *guard* ] `,` ... `])`
+///
+/// This is a validating pass-through: the expression is evaluated once, checked with `assert_matches_complement!`
+/// and returned unchanged.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// #[derive(Debug, PartialEq)]
+/// enum Input {
+///     Empty,
+///     Malformed(u8),
+///     Text(&'static str),
+/// }
+///
+/// fn main() {
+///     let input = reject_match!(Input::Text("hi"), [Input::Empty, Input::Malformed(_)]);
+///     assert_eq!(input, Input::Text("hi"));
+/// }
+/// ```
+#[macro_export]
+macro_rules! reject_match {
+    ($expression:expr, [$($($pattern:pat)|+ $(if $ifguard:expr)?),+ $(,)?]) => {
+        match $expression {
+            value => {
+                $crate::assert_matches_complement!(value, [$($($pattern)|+ $(if $ifguard)?),+]);
+                value
+            }
+        }
+    };
+}
+
+/// A type whose values can be checked against an expected shape, as used by `check_matches!`.
+///
+/// This lets generic code require `T: PatternCheck` where the pattern depends on the type.
+/// [`check`](PatternCheck::check) is usually implemented with `define_pattern_check!`.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// use matches2::PatternCheck;
+///
+/// #[derive(Debug)]
+/// enum Reply {
+///     Ok(u16),
+///     Error(String),
+/// }
+///
+/// define_pattern_check!(Reply, Reply::Ok(200..=299));
+///
+/// fn successful<T: PatternCheck>(replies: &[T]) -> usize {
+///     replies.iter().filter(|reply| check_matches!(reply)).count()
+/// }
+///
+/// fn main() {
+///     assert_eq!(successful(&[Reply::Ok(204), Reply::Error("timeout".into()), Reply::Ok(404)]), 1);
+/// }
+/// ```
+pub trait PatternCheck {
+    /// Returns true if the value has the expected shape.
+    fn check(&self) -> bool;
+}
+
+impl<T: PatternCheck + ?Sized> PatternCheck for &T {
+    fn check(&self) -> bool {
+        (**self).check()
+    }
+}
+
+/// Check if a value has the expected shape of its type, with [`PatternCheck::check`].
+///
+/// Syntax: `check_matches!(` *value* `)`
+///
+/// The value is borrowed, so this works for any place or expression whose type implements
+/// `PatternCheck`, including references.
+/// See [`PatternCheck`] for examples.
+#[macro_export]
+macro_rules! check_matches {
+    ($value:expr $(,)?) => {
+        $crate::PatternCheck::check(&$value)
+    };
+}
+
+/// Implement [`PatternCheck`] for a type with a refutable pattern.
+///
+/// Syntax: `define_pattern_check!(` *type* `,` *pattern* [ `if` *guard* ] `)`
+///
+/// [`check`](PatternCheck::check) is implemented with `matches!(*self,` *pattern* `)`.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// struct Port(u16);
+///
+/// define_pattern_check!(Port, Port(p) if p >= 1024);
+///
+/// fn main() {
+///     assert!(check_matches!(Port(8080)));
+///     assert!(!check_matches!(Port(80)));
+/// }
+/// ```
+#[macro_export]
+macro_rules! define_pattern_check {
+    ($type:ty, $($pattern:pat)|+ $(if $ifguard:expr)?) => {
+        impl $crate::PatternCheck for $type {
+            fn check(&self) -> bool {
+                $crate::matches!(*self, $($pattern)|+ $(if $ifguard)?)
+            }
+        }
+    };
+}
+
+/// Applies a fallible transform to the bindings of a pattern, or returns a given error if the pattern does not match.
+///
+/// Syntax: `map_match_res!(` *expression* `,` *pattern* [ `if` *guard* ] `=>` *transform* `,` *error* `)`
+///
+/// If the pattern matches, the macro evaluates to *transform*, which must be a `Result`.
+/// Otherwise, it evaluates to `Err(`*error*`)`, where *error* is only evaluated on a mismatch.
+///
+/// # Examples
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// #[derive(Debug, PartialEq)]
+/// enum Error {
+///     NotANumber,
+///     NotAField,
+/// }
+///
+/// fn value(line: &str) -> Result<u32, Error> {
+///     let parts: Vec<&str> = line.splitn(2, '=').collect();
+///     map_match_res!(parts[..], [_, value] => value.parse().map_err(|_| Error::NotANumber), Error::NotAField)
+/// }
+///
+/// fn main() {
+///     assert_eq!(value("port=80"), Ok(80));
+///     assert_eq!(value("port=http"), Err(Error::NotANumber));
+///     assert_eq!(value("port"), Err(Error::NotAField));
+/// }
+/// ```
+#[macro_export]
+macro_rules! map_match_res {
+    ($expression:expr, $($pattern:pat)|+ $(if $ifguard:expr)? => $transform:expr, $error:expr $(,)?) => {
+        match $expression {
+            $($pattern)|+ $(if $ifguard)? => $transform,
+            #[allow(unreachable_patterns)]
+            _ => $crate::__private::Result::Err($error),
+        }
+    };
+}
+
+/// Assert that the float fields of a struct are each within a tolerance of an expected value.
+///
+/// Syntax: `assert_matches_approx_struct!(` *expression* `,` *path* `{` *field* `:` *expected* `;` *tolerance* `,` ... `})`
+///
+/// The expression is borrowed and must be of the type *path*; the fields that are not listed are ignored.
+/// Each field passes if its distance to *expected* is at most *tolerance*, so `NaN` never passes. `±` is not a
+/// Rust token, so the tolerance is separated by a `;`. The panic message reports the first field outside its
+/// tolerance, with its actual and expected values and the tolerance.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// struct Reading {
+///     celsius: f64,
+///     humidity: f32,
+///     sensor: u8,
+/// }
+///
+/// fn main() {
+///     let reading = Reading { celsius: 21.52, humidity: 0.405, sensor: 3 };
+///     assert_matches_approx_struct!(reading, Reading { celsius: 21.5; 0.05, humidity: 0.4; 0.01 });
+/// }
+/// ```
+#[macro_export]
+macro_rules! assert_matches_approx_struct {
+    ($expression:expr, $path:path { $($field:ident : $expected:expr ; $tolerance:expr),+ $(,)? }) => {
+        match &$expression {
+            actual => {
+                let actual: &$path = actual;
+                $(
+                    match (actual.$field, $expected, $tolerance) {
+                        (value, expected, tolerance) => {
+                            let distance = if value > expected { value - expected } else { expected - value };
+                            let within = distance <= tolerance;
+                            if !within {
+                                panic!(
+                                    "assertion failed: field `{}` of `{}` is outside the tolerance\n    actual: {:?}\n  expected: {:?}\n tolerance: {:?}",
+                                    stringify!($field),
+                                    stringify!($expression),
+                                    value,
+                                    expected,
+                                    tolerance
+                                )
+                            }
+                        }
+                    }
+                )+
+            }
+        }
+    };
+}
+
+/// Replaces the value of a map entry if it matches a refutable pattern.
+///
+/// Syntax: `entry_match!(` *map* `,` *key* `,` *pattern* [ `if` *guard* ] `=>` *new value* `)`
+///
+/// The value is looked up with *map*`.get_mut(&`*key*`)`, so this works with `HashMap`, `BTreeMap` and other maps
+/// with such a method. If the key is present and its value matches, *new value* is evaluated and replaces it,
+/// and the macro returns `true`. The bindings are references into the old value through the default binding mode.
+/// Otherwise, the map is left unchanged and the macro returns `false`.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// use std::collections::HashMap;
+///
+/// #[derive(Debug, PartialEq)]
+/// enum Job {
+///     Queued,
+///     Running { attempt: u8 },
+///     Done,
+/// }
+///
+/// fn main() {
+///     let mut jobs = HashMap::new();
+///     jobs.insert(1, Job::Running { attempt: 1 });
+///     assert!(entry_match!(jobs, 1, Job::Running { attempt } if *attempt < 3 => Job::Running { attempt: attempt + 1 }));
+///     assert_eq!(jobs[&1], Job::Running { attempt: 2 });
+///     assert!(!entry_match!(jobs, 2, Job::Queued => Job::Done));
+/// }
+/// ```
+#[macro_export]
+macro_rules! entry_match {
+    ($map:expr, $key:expr, $($pattern:pat)|+ $(if $ifguard:expr)? => $new:expr) => {
+        match $map.get_mut(&$key) {
+            $crate::__private::Option::Some(slot) => {
+                let new = match &*slot {
+                    $($pattern)|+ $(if $ifguard)? => $crate::__private::Option::Some($new),
+                    #[allow(unreachable_patterns)]
+                    _ => $crate::__private::Option::None,
+                };
+                match new {
+                    $crate::__private::Option::Some(new) => {
+                        *slot = new;
+                        true
+                    }
+                    $crate::__private::Option::None => false,
+                }
+            }
+            $crate::__private::Option::None => false,
+        }
+    };
+}
+
+/// Assert that exactly one of a list of labeled patterns matches an expression, returning its label.
+///
+/// Syntax: `assert_unique_match!(` *expression* `, {` *label* `=>` *pattern* [ `if` *guard* ] `,` ... `})`
+///
+/// Every pattern is tried, so overlapping patterns in a classifier are caught. The labels must implement `Debug`,
+/// and the panic message reports the value and either that no pattern matched or the labels of all that did.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     let status = 404;
+///     let class = assert_unique_match!(status, {
+///         "success" => 200..=299,
+///         "client error" => 400..=499,
+///         "server error" => 500..=599,
+///     });
+///     assert_eq!(class, "client error");
+/// }
+/// ```
+#[macro_export]
+macro_rules! assert_unique_match {
+    ($expression:expr, { $($label:expr => $($pattern:pat)|+ $(if $ifguard:expr)?),+ $(,)? }) => {
+        match $expression {
+            ref value => {
+                let matched = [$($crate::matches!(#[allow(unreachable_patterns)] *value, $($pattern)|+ $(if $ifguard)?)),+];
+                let labels = [$($label),+];
+                match matched.iter().filter(|&&matched| matched).count() {
+                    0 => panic!(
+                        "assertion failed: `{}` matches none of the labeled patterns",
+                        $crate::__private::Value(value)
+                    ),
+                    1 => {
+                        let index = matched.iter().position(|&matched| matched).unwrap();
+                        $crate::__private::IntoIterator::into_iter(labels).nth(index).unwrap()
+                    }
+                    _ => panic!(
+                        "assertion failed: `{}` matches more than one labeled pattern: {}",
+                        $crate::__private::Value(value),
+                        $crate::__private::Matched(&labels, &matched)
+                    ),
+                }
+            }
+        }
+    };
+}
+
+/// Check if a variable matches a refutable pattern, rejecting guards that refer to the variable itself.
+///
+/// Syntax: `strict_matches!(` *variable* `,` *pattern* [ `if` *guard* ] `)`
+///
+/// Returns the same boolean as `matches!`. A guard that tests the scrutinee again, instead of the bindings
+/// of the pattern, is usually a copy-paste error, as in `strict_matches!(x, Some(0) if x == Some(1))`,
+/// which can never be true. Within the guard, the variable is shadowed by a value of the unit type
+/// `GuardUsesScrutinee`, so such a guard fails to compile with an error naming that type.
+/// The guard can still use the bindings and any other outer state; a binding with the same name as the
+/// variable is shadowed too. Other expressions than variables are matched like `matches!`.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     let limit = 10;
+///     let x = Some(3);
+///     assert!(strict_matches!(x, Some(n) if n < limit));
+/// }
+/// ```
+///
+/// Testing the scrutinee in the guard is an error:
+///
+/// ```compile_fail
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     let x = Some(0);
+///     strict_matches!(x, Some(0) if x == Some(1));
+/// }
+/// ```
+///
+/// As with `matches!`, so is a guard using a binding the pattern does not have:
+///
+/// ```compile_fail
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     let x = Some(0);
+///     strict_matches!(x, Some(_) if n > 0);
+/// }
+/// ```
+#[macro_export]
+macro_rules! strict_matches {
+    ($variable:ident, $($pattern:pat)|+ if $ifguard:expr) => {
+        $crate::matches!($variable, $($pattern)|+ if {
+            #[allow(unused_variables)]
+            let $variable = $crate::__private::GuardUsesScrutinee;
+            $ifguard
+        })
+    };
+    ($expression:expr, $($pattern:pat)|+ $(if $ifguard:expr)?) => {
+        $crate::matches!($expression, $($pattern)|+ $(if $ifguard)?)
+    };
+}
+
+/// An accumulator of assertions that reports all the failed ones at once, as used by `batch_assert_matches!`.
+///
+/// Failed assertions are recorded instead of panicking, and [`finish`](MatchBatch::finish) panics once with
+/// a report of all of them, so that a test shows every mismatch rather than only the first.
+/// It requires the `std` feature.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// use matches2::MatchBatch;
+///
+/// fn main() {
+///     let mut batch = MatchBatch::new();
+///     batch_assert_matches!(&mut batch, "1".parse::<u8>(), Ok(1));
+///     batch_assert_matches!(&mut batch, Some(2), Some(1..=9));
+///     batch.finish();
+/// }
+/// ```
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, Default)]
+#[must_use = "a `MatchBatch` does not report failures unless `finish` is called"]
+pub struct MatchBatch {
+    checks: usize,
+    failures: Vec<String>,
+}
+
+#[cfg(feature = "std")]
+impl MatchBatch {
+    /// Start a batch with no assertions yet.
+    pub fn new() -> MatchBatch {
+        MatchBatch::default()
+    }
+
+    /// Record an assertion that passed.
+    pub fn pass(&mut self) {
+        self.checks += 1;
+    }
+
+    /// Record an assertion that failed, with a description of the failure.
+    pub fn fail(&mut self, failure: String) {
+        self.checks += 1;
+        self.failures.push(failure);
+    }
+
+    /// Return the descriptions of the failed assertions so far.
+    pub fn failures(&self) -> &[String] {
+        &self.failures
+    }
+
+    /// Assert that all the recorded assertions passed.
+    ///
+    /// Otherwise, this panics with a message like `assertion failed: 2 of 5 batched assertions failed`,
+    /// followed by the failures, one per line.
+    pub fn finish(self) {
+        if !self.failures.is_empty() {
+            let mut message = format!(
+                "assertion failed: {} of {} batched assertions failed",
+                self.failures.len(),
+                self.checks
+            );
+            for failure in &self.failures {
+                message.push_str("\n  ");
+                message.push_str(failure);
+            }
+            panic!("{}", message);
+        }
+    }
+}
+
+/// Assert that an expression matches a refutable pattern, recording a failure in a [`MatchBatch`] instead of panicking.
+///
+/// Syntax: `batch_assert_matches!(&mut` *batch* `,` *expression* `,` *pattern* [ `if` *guard* ] `)`
+///
+/// A mismatch is recorded with the expression, its value and the pattern, and reported by `MatchBatch::finish`.
+/// This macro requires the `std` feature.
+/// See [`MatchBatch`] for examples.
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! batch_assert_matches {
+    (&mut $batch:expr, $expression:expr, $($pattern:pat)|+ $(if $ifguard:expr)?) => {
+        match $expression {
+            $($pattern)|+ $(if $ifguard)? => $crate::MatchBatch::pass(&mut $batch),
+            #[allow(unreachable_patterns)]
+            ref value => $crate::MatchBatch::fail(
+                &mut $batch,
+                format!(
+                    "`{}`: `{}` does not match `{}`",
+                    stringify!($expression),
+                    $crate::__private::Value(value),
+                    stringify!($($pattern)|+ $(if $ifguard)?)
+                ),
+            ),
+        }
+    };
+}
+
+/// Define a `const fn` classifying bytes with a list of pattern arms.
+///
+/// Syntax: `define_byte_classifier!(` [ *attributes* ] [ *visibility* ] `fn` *name* `(` *byte* `) ->` *type* `{`
+/// *pattern* `=>` *class* `,` ... `})`
+///
+/// The function is generated as a single `match` on the `u8`, which the compiler can turn into a lookup table,
+/// and can be called in const contexts, e.g. to build tables for a parser. The arms are tried in order, so
+/// overlapping ranges are classified by the first arm, and they must be exhaustive, usually with a last `_` arm
+/// for the default class. The classes must be constant expressions.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// #[derive(Clone, Copy, Debug, PartialEq)]
+/// pub enum Class {
+///     Digit,
+///     Letter,
+///     Space,
+///     Other,
+/// }
+///
+/// define_byte_classifier!(pub fn classify(byte) -> Class {
+///     b'0'..=b'9' => Class::Digit,
+///     b'a'..=b'z' | b'A'..=b'Z' => Class::Letter,
+///     b' ' | b'\t' | b'\r' | b'\n' => Class::Space,
+///     _ => Class::Other,
+/// });
+///
+/// const NEWLINE: Class = classify(b'\n');
+///
+/// fn main() {
+///     assert_eq!(NEWLINE, Class::Space);
+///     assert_eq!(classify(b'7'), Class::Digit);
+/// }
+/// ```
+#[macro_export]
+macro_rules! define_byte_classifier {
+    (
+        $(#[$attr:meta])* $vis:vis fn $name:ident($byte:ident) -> $class_type:ty {
+            $($($pattern:pat)|+ => $class:expr),+ $(,)?
+        }
+    ) => {
+        $crate::const_matcher!($(#[$attr])* $vis fn $name($byte: u8) -> $class_type {
+            $($($pattern)|+ => $class),+
+        });
+    };
+}
+
+/// Assert that two iterables yield the same enum variants in the same order, ignoring their fields.
+///
+/// Syntax: `assert_iter_same_variants!(` *left* `,` *right* `)`
+///
+/// The elements are compared pairwise with `mem::discriminant`, so they may be enum values or references to them,
+/// and the two sides may differ in that. The panic message reports the index and both values of the first pair
+/// of different variants, or which side has more elements than the other.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// #[derive(Debug)]
+/// enum Event {
+///     Connect(u32),
+///     Send(Vec<u8>),
+///     Close,
+/// }
+///
+/// fn main() {
+///     let recorded = vec![Event::Connect(1), Event::Send(vec![1, 2]), Event::Close];
+///     let replayed = vec![Event::Connect(7), Event::Send(vec![]), Event::Close];
+///     assert_iter_same_variants!(recorded.iter(), replayed);
+/// }
+/// ```
+#[macro_export]
+macro_rules! assert_iter_same_variants {
+    ($left:expr, $right:expr $(,)?) => {{
+        use $crate::__private::VariantOf as _;
+        // Matching keeps the temporaries of both expressions alive, like in `matches!`.
+        match ($crate::__private::IntoIterator::into_iter($left), $crate::__private::IntoIterator::into_iter($right)) {
+            (mut left, mut right) => {
+                let mut index = 0usize;
+                loop {
+                    match (left.next(), right.next()) {
+                        ($crate::__private::Option::Some(l), $crate::__private::Option::Some(r)) => {
+                            if l.variant_of() != r.variant_of() {
+                                panic!(
+                                    "assertion failed: element {} differs in variant\n  left: {}\n right: {}",
+                                    index,
+                                    $crate::__private::Value(&l),
+                                    $crate::__private::Value(&r)
+                                )
+                            }
+                        }
+                        ($crate::__private::Option::Some(l), $crate::__private::Option::None) => panic!(
+                            "assertion failed: `{}` has more elements than `{}`, which ended after {}; next left: {}",
+                            stringify!($left),
+                            stringify!($right),
+                            index,
+                            $crate::__private::Value(&l)
+                        ),
+                        ($crate::__private::Option::None, $crate::__private::Option::Some(r)) => panic!(
+                            "assertion failed: `{}` has more elements than `{}`, which ended after {}; next right: {}",
+                            stringify!($right),
+                            stringify!($left),
+                            index,
+                            $crate::__private::Value(&r)
+                        ),
+                        ($crate::__private::Option::None, $crate::__private::Option::None) => break,
+                    }
+                    index += 1;
+                }
+            }
+        }
+    }};
+}
+
+/// Match an expression against arms with their own bindings, panicking like `unwrap_match!` if none matches.
+///
+/// Syntax: `arm_match!(` *expression* `,` *pattern* [ `if` *guard* ] `=>` *result* `,` ... [ `, _ =>` *default* ] `)`
+///
+/// Unlike the alternatives of a single pattern, each arm can bind different names for its own *result*.
+/// This expands to a `match` with the given arms. If the last arm is not a `_` arm, a mismatch panics
+/// with a default message that lists the patterns.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// #[derive(Debug)]
+/// enum Shape {
+///     Circle { radius: f64 },
+///     Square(f64),
+///     Point,
+/// }
+///
+/// fn main() {
+///     let shape = Shape::Square(2.0);
+///     let area = arm_match!(shape, Shape::Circle { radius } => 3.14 * radius * radius, Shape::Square(side) => side * side);
+///     assert_eq!(area, 4.0);
+///     assert_eq!(arm_match!(Shape::Point, Shape::Square(side) => side, _ => 0.0), 0.0);
+/// }
+/// ```
+#[macro_export]
+macro_rules! arm_match {
+    (@arms $expression:expr, [$($arms:tt)*] [$($patterns:tt)*] _ => $default:expr $(,)?) => {
+        match $expression {
+            $($arms)*
+            _ => $default,
+        }
+    };
+    (@arms $expression:expr, [$($arms:tt)*] [$($patterns:tt)*] $($pattern:pat)|+ $(if $ifguard:expr)? => $result:expr $(, $($rest:tt)*)?) => {
+        $crate::arm_match!(
+            @arms $expression,
+            [$($arms)* $($pattern)|+ $(if $ifguard)? => $result,]
+            [$($patterns)* "`, `", stringify!($($pattern)|+ $(if $ifguard)?),]
+            $($($rest)*)?
+        )
+    };
+    (@arms $expression:expr, [$($arms:tt)*] [$($patterns:tt)*]) => {
+        match $expression {
+            $($arms)*
+            #[allow(unreachable_patterns)]
+            ref value => panic!(
+                "assertion failed: `{}` does not match any of `{}`",
+                $crate::__private::Value(value),
+                // Skips the leading separator.
+                &concat!($($patterns)*)[4..]
+            ),
+        }
+    };
+    ($expression:expr, $($arms:tt)+) => {
+        $crate::arm_match!(@arms $expression, [] [] $($arms)+)
+    };
+}
+
+/// Match an expression against exhaustive arms, counting in a histogram which arm matched.
+///
+/// Syntax: `profile_match!(` *histogram* `,` *expression* `, {` *pattern* [ `if` *guard* ] `=>` *result* `,` ... `})`
+///
+/// *histogram* is a mutable array, slice or `Vec` of `u64`, with one counter per arm in order. The counter of
+/// the matching arm is incremented before its result is evaluated. The histogram must have as many counters as
+/// there are arms, which is checked at run time.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     let mut histogram = [0; 3];
+///     for byte in b"a1 b2" {
+///         profile_match!(histogram, byte, {
+///             b'a'..=b'z' => (),
+///             b'0'..=b'9' => (),
+///             _ => (),
+///         });
+///     }
+///     assert_eq!(histogram, [2, 2, 1]);
+/// }
+/// ```
+#[macro_export]
+macro_rules! profile_match {
+    (@arms $histogram:ident, $expression:expr, ($count:expr) [$($arms:tt)*]) => {{
+        let $histogram: &mut [u64] = $histogram;
+        assert_eq!($histogram.len(), $count, "the histogram of `profile_match!` must have a counter for each arm");
+        match $expression {
+            $($arms)*
+        }
+    }};
+    (@arms $histogram:ident, $expression:expr, ($index:expr) [$($arms:tt)*] $($pattern:pat)|+ $(if $ifguard:expr)? => $result:expr $(, $($rest:tt)*)?) => {
+        $crate::profile_match!(
+            @arms $histogram,
+            $expression,
+            ($index + 1)
+            [$($arms)* $($pattern)|+ $(if $ifguard)? => {
+                $histogram[$index] += 1;
+                $result
+            }]
+            $($($rest)*)?
+        )
+    };
+    ($histogram:expr, $expression:expr, { $($arms:tt)+ }) => {
+        match &mut $histogram[..] {
+            histogram => $crate::profile_match!(@arms histogram, $expression, (0) [] $($arms)+),
+        }
+    };
+}
+
+/// The failure reported by `match_guard_report!`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GuardFailure {
+    /// The value does not match the structure of the pattern, so no guard was evaluated.
+    Mismatch,
+    /// The guard with this index, counting from zero, is the first one that is false.
+    Guard(usize),
+}
+
+/// Match an expression against a pattern and a list of guards, reporting which guard failed.
+///
+/// Syntax: `match_guard_report!(` *expression* `,` *pattern* `, [` *guard* `,` ... `])`
+///
+/// Returns `Ok(())` if the pattern matches and every guard is true. The guards can use the bindings of the
+/// pattern and are evaluated in order, stopping at the first false one, whose index is returned as
+/// `Err(GuardFailure::Guard(index))`. A value that does not match the pattern itself returns
+/// `Err(GuardFailure::Mismatch)`.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// use matches2::GuardFailure;
+///
+/// fn main() {
+///     let point = Some((3, -1));
+///     assert_eq!(match_guard_report!(point, Some((x, y)), [x > 0, y > 0, x + y < 10]), Err(GuardFailure::Guard(1)));
+///     assert_eq!(match_guard_report!(point, Some((x, _)), [x > 0, x < 10]), Ok(()));
+///     assert_eq!(match_guard_report!(None::<(i32, i32)>, Some((x, _)), [x > 0]), Err(GuardFailure::Mismatch));
+/// }
+/// ```
+#[macro_export]
+macro_rules! match_guard_report {
+    (@guards ($index:expr)) => {
+        $crate::__private::Result::Ok(())
+    };
+    (@guards ($index:expr) $guard:expr, $($rest:expr,)*) => {
+        if $guard {
+            $crate::match_guard_report!(@guards ($index + 1) $($rest,)*)
+        } else {
+            $crate::__private::Result::Err($crate::GuardFailure::Guard($index))
+        }
+    };
+    ($expression:expr, $($pattern:pat)|+, [$($guard:expr),* $(,)?]) => {
+        match $expression {
+            $($pattern)|+ => $crate::match_guard_report!(@guards (0) $($guard,)*),
+            #[allow(unreachable_patterns)]
+            _ => $crate::__private::Result::Err($crate::GuardFailure::Mismatch),
+        }
+    };
+}
+
+/// Assert that repeatedly applying a step function to a value reaches a value matching a refutable pattern.
+///
+/// Syntax: `assert_matches_fixpoint!(` *initial value* `,` *step* `,` *pattern* [ `if` *guard* ] `,` *max iterations* `)`
+///
+/// *step* is a closure taking the value and returning the next one. The initial value is matched first, then
+/// the step is applied until the value matches, at most *max iterations* times, and the macro evaluates to the
+/// number of steps that were applied. If the value still does not match, this panics with the last value and
+/// the number of iterations.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     let collatz = |n: u64| if n % 2 == 0 { n / 2 } else { 3 * n + 1 };
+///     assert_eq!(assert_matches_fixpoint!(6, collatz, 1, 10), 8);
+/// }
+/// ```
+#[macro_export]
+macro_rules! assert_matches_fixpoint {
+    ($init:expr, $step:expr, $($pattern:pat)|+ $(if $ifguard:expr)?, $max_iters:expr $(,)?) => {{
+        #[allow(unused_mut)]
+        let mut step = $step;
+        let max_iters: usize = $max_iters;
+        let mut value = $init;
+        let mut iterations: usize = 0;
+        loop {
+            match value {
+                $($pattern)|+ $(if $ifguard)? => break iterations,
+                #[allow(unreachable_patterns)]
+                _ => (),
+            }
+            if iterations == max_iters {
+                panic!(
+                    "assertion failed: `{}` does not match `{}` after {} iterations",
+                    $crate::__private::Value(&value),
+                    stringify!($($pattern)|+ $(if $ifguard)?),
+                    iterations
+                );
+            }
+            value = step(value);
+            iterations += 1;
+        }
+    }};
+}
+
+/// Destructure a struct or an enum variant with named fields and build another struct from its fields.
+///
+/// Syntax: `destructure_match!(` *expression* `,` *source* `{` *field* `,` ... [ `..` ] `} =>` *target* `{`
+/// *field* [ `:` *value* ] `,` ... `})`
+///
+/// The listed source fields are bound by name and are in scope for the values of the target fields, which
+/// may also be tuple struct indices such as `0`, or shorthands for a source field of the same name.
+/// Like `unwrap_match!`, this panics with the standard message if the expression is another variant than
+/// *source*, so its type must implement `Debug`.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// #[derive(Debug)]
+/// struct Celsius { degrees: f64, station: &'static str }
+/// struct Fahrenheit(f64);
+///
+/// fn main() {
+///     let reading = Celsius { degrees: 20.0, station: "north" };
+///     let converted = destructure_match!(reading, Celsius { degrees, .. } => Fahrenheit { 0: degrees * 1.8 + 32.0 });
+///     assert_eq!(converted.0, 68.0);
+/// }
+/// ```
+#[macro_export]
+macro_rules! destructure_match {
+    ($expression:expr, $source:path { $($field:ident),* $(,)? } => $target:path { $($target_field:tt $(: $value:expr)?),* $(,)? }) => {
+        $crate::unwrap_match!($expression, $source { $($field),* } => { $target { $($target_field $(: $value)?),* } })
+    };
+    ($expression:expr, $source:path { $($field:ident,)* .. } => $target:path { $($target_field:tt $(: $value:expr)?),* $(,)? }) => {
+        $crate::unwrap_match!($expression, $source { $($field,)* .. } => { $target { $($target_field $(: $value)?),* } })
+    };
+}
+
+/// Find the span of a slice from an element matching a start pattern to the next element matching an end pattern.
+///
+/// Syntax: `span_match!(` *slice* `,` *start pattern* [ `if` *guard* ] `,` *end pattern* [ `if` *guard* ] `=>` *result* `)`
+///
+/// The start pattern is matched against each element until one matches, and the end pattern against each
+/// element after it until one matches. Returns `Option::Some((`*result*`, start..=end))` with the indices of
+/// both elements, or `Option::None` if no element matches the start pattern, or none after it matches the end
+/// pattern. *slice* may be anything that coerces to a `&[T]`, and the bindings in the patterns are references
+/// into it. The end guard and the result can use the bindings of both patterns.
+///
+/// # Examples
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     let input: &[u8] = b"let s = \"quoted\";";
+///     let (quote, span) = span_match!(input, open @ (b'"' | b'\''), close if close == open => *open).unwrap();
+///     assert_eq!(quote, b'"');
+///     assert_eq!(&input[span], b"\"quoted\"");
+/// }
+/// ```
+#[macro_export]
+macro_rules! span_match {
+    ($slice:expr, $($start:pat)|+ $(if $startguard:expr)?, $($end:pat)|+ $(if $endguard:expr)? => $result:expr) => {
+        match $slice {
+            slice => {
+                let slice: &[_] = slice;
+                let mut span = $crate::__private::Option::None;
+                for (start, element) in slice.iter().enumerate() {
+                    match element {
+                        $($start)|+ $(if $startguard)? => {
+                            for (end, element) in slice.iter().enumerate().skip(start + 1) {
+                                match element {
+                                    $($end)|+ $(if $endguard)? => {
+                                        span = $crate::__private::Option::Some(($result, start..=end));
+                                        break;
+                                    }
+                                    #[allow(unreachable_patterns)]
+                                    _ => ()
+                                }
+                            }
+                            break;
+                        }
+                        #[allow(unreachable_patterns)]
+                        _ => ()
+                    }
+                }
+                span
+            }
+        }
+    };
+}
+
+/// Match the inner value of an `either::Either` with a pattern for each side.
+///
+/// Syntax: `either_match!(` *expression* `, Left(` *pattern* `)` [ `if` *guard* ] `=>` *result* `, Right(`
+/// *pattern* `)` [ `if` *guard* ] `=>` *result* `)`
+///
+/// Both results must have the same type, which the macro evaluates to. If the inner value does not match the
+/// pattern of its side, this panics with a message naming the side, e.g.
+/// ``assertion failed: left value `3` does not match `0..=2` ``.
+/// This macro requires the `either` feature.
+///
+/// # Examples
+///
+#[cfg_attr(feature = "either", doc = "```")]
+#[cfg_attr(not(feature = "either"), doc = "```ignore")]
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// use either::Either;
+///
+/// fn main() {
+///     let value: Either<u8, &str> = Either::Right("42");
+///     let n = either_match!(value, Left(n) => u32::from(n), Right(s) if !s.is_empty() => s.parse().unwrap());
+///     assert_eq!(n, 42);
+/// }
+/// ```
+#[cfg(feature = "either")]
+#[macro_export]
+macro_rules! either_match {
+    ($expression:expr, Left($($lpattern:pat)|+) $(if $lguard:expr)? => $lresult:expr,
+        Right($($rpattern:pat)|+) $(if $rguard:expr)? => $rresult:expr $(,)?) => {
+        match $expression {
+            $crate::__private::either::Either::Left($($lpattern)|+) $(if $lguard)? => $lresult,
+            $crate::__private::either::Either::Right($($rpattern)|+) $(if $rguard)? => $rresult,
+            #[allow(unreachable_patterns)]
+            $crate::__private::either::Either::Left(ref value) => panic!(
+                "assertion failed: left value `{}` does not match `{}`",
+                $crate::__private::Value(value),
+                stringify!($($lpattern)|+ $(if $lguard)?)
+            ),
+            #[allow(unreachable_patterns)]
+            $crate::__private::either::Either::Right(ref value) => panic!(
+                "assertion failed: right value `{}` does not match `{}`",
+                $crate::__private::Value(value),
+                stringify!($($rpattern)|+ $(if $rguard)?)
+            ),
+        }
+    };
+}
+
+/// Declare a thread-local slot for `capture_match!` to store a value in.
+///
+/// Syntax: `define_capture_slot!(` [ *attributes* ] [ *visibility* ] `static` *name* `:` *type* `;)`
+///
+/// The slot is a `thread_local!` `Cell` holding an `Option` of the type, initially empty, so each test thread has
+/// its own. This macro requires the `std` feature.
+/// See `capture_match!` for examples.
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! define_capture_slot {
+    ($(#[$attr:meta])* $vis:vis static $name:ident : $type:ty $(;)?) => {
+        $crate::__private::thread_local! {
+            $(#[$attr])*
+            $vis static $name: $crate::__private::Cell<$crate::__private::Option<$type>> =
+                const { $crate::__private::Cell::new($crate::__private::Option::None) };
+        }
+    };
+}
+
+/// Assert that an expression matches a refutable pattern, storing the result in a capture slot.
+///
+/// Syntax: `capture_match!(` *slot* `,` *expression* `,` *pattern* [ `if` *guard* ] `=>` *result* `)`
+///
+/// The slot is declared with `define_capture_slot!`. Like `unwrap_match!`, this panics if the pattern does
+/// not match; otherwise the result replaces any value in the slot, to be read by a later step of the test on
+/// the same thread with `take_captured!(`*slot*`)`, which empties the slot and evaluates to an `Option`.
+/// These macros require the `std` feature.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// define_capture_slot!(static SESSION: u32;);
+///
+/// fn setup() {
+///     let response: Result<u32, String> = Ok(7);
+///     capture_match!(SESSION, response, Ok(id) if id > 0 => id);
+/// }
+///
+/// fn main() {
+///     setup();
+///     assert_eq!(take_captured!(SESSION), Some(7));
+///     assert_eq!(take_captured!(SESSION), None);
+/// }
+/// ```
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! capture_match {
+    ($slot:path, $expression:expr, $($pattern:pat)|+ $(if $ifguard:expr)? => $result:expr) => {{
+        let captured = $crate::unwrap_match!($expression, $($pattern)|+ $(if $ifguard)? => $result);
+        $slot.with(|slot| slot.set($crate::__private::Option::Some(captured)));
+    }};
+}
+
+/// Take the value stored in a capture slot by `capture_match!`, leaving it empty.
+///
+/// Syntax: `take_captured!(` *slot* `)`
+///
+/// Evaluates to `Option::None` if nothing was captured on this thread since the slot was last taken.
+/// This macro requires the `std` feature.
+/// See `capture_match!` for examples.
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! take_captured {
+    ($slot:path $(,)?) => {
+        $slot.with(|slot| slot.take())
+    };
+}
+
+/// Assert that a stream yields items matching a list of patterns in order, and then ends.
+///
+/// Syntax: `assert_stream_matches!(` *stream* `, [` *pattern* [ `if` *guard* ] `,` ... `])`
+///
+/// The stream is pinned and polled to completion with `futures::StreamExt::next`, so the macro must be used
+/// in an async context, such as an async test. The panic message reports the index and the value of the first
+/// item that does not match its pattern, how many items there were if the stream ends early, or the first
+/// extra item if it does not end.
+/// This macro requires the `futures` feature.
+///
+/// # Examples
+///
+#[cfg_attr(feature = "futures", doc = "```")]
+#[cfg_attr(not(feature = "futures"), doc = "```ignore")]
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// fn main() {
+///     futures::executor::block_on(async {
+///         let stream = futures::stream::iter(vec![Ok(1), Ok(2), Err("closed")]);
+///         assert_stream_matches!(stream, [Ok(1), Ok(n) if n > 1, Err(_)]);
+///     });
+/// }
+/// ```
+#[cfg(feature = "futures")]
+#[macro_export]
+macro_rules! assert_stream_matches {
+    ($stream:expr, [$($($pattern:pat)|+ $(if $ifguard:expr)?),* $(,)?]) => {{
+        let mut stream = $crate::__private::pin!($stream);
+        #[allow(unused_variables)]
+        let expected = <[&str]>::len(&[$(stringify!($($pattern)|+)),*]);
+        #[allow(unused_mut)]
+        let mut index = 0usize;
+        $(
+            match $crate::__private::futures::StreamExt::next(&mut stream).await {
+                $crate::__private::Option::Some(item) => match item {
+                    $($pattern)|+ $(if $ifguard)? => (),
+                    #[allow(unreachable_patterns)]
+                    ref item => panic!(
+                        "assertion failed: item {} (`{}`) of the stream does not match `{}`",
+                        index,
+                        $crate::__private::Value(item),
+                        stringify!($($pattern)|+ $(if $ifguard)?)
+                    ),
+                },
+                $crate::__private::Option::None => panic!(
+                    "assertion failed: expected {} items, but the stream ended after {}",
+                    expected,
+                    index
+                ),
+            }
+            index += 1;
+        )*
+        if let $crate::__private::Option::Some(ref item) = $crate::__private::futures::StreamExt::next(&mut stream).await {
+            panic!(
+                "assertion failed: expected {} items, but the stream yielded another one: `{}`",
+                index,
+                $crate::__private::Value(item)
+            )
+        }
+    }};
+}
+
+/// Assert that an expression matches a nested pattern, explaining which component of the value does not match.
+///
+/// Syntax: `assert_matches_explain!(` *expression* `,` *pattern* [ `if` *guard* ] `)`
+///
+/// If the value does not match, the pattern is decomposed into layers of tuple struct, struct and tuple
+/// patterns, which are matched one at a time from the outside in. The panic message has the standard first line,
+/// followed by the layers that matched, with `_` in place of the first component that does not match, and that
+/// component's value and pattern:
+///
+/// ```text
+/// assertion failed: `Some(Ok(Point { x: 5, y: 0 }))` does not match `Some(Ok(Point { x: 0, .. }))`
+///   matched `Some(Ok(Point { x: _, .. }))` but field `x` was `5`, expected `0`
+/// ```
+///
+/// Other patterns, such as literals, ranges, bindings, `|` alternatives and `..` rest patterns, are matched
+/// as a whole, so components after a `..` in a tuple pattern are not explained. If every layer matches, the
+/// message says that the guard was false. Tuple structs and tuples can have up to 16 fields.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate matches2;
+///
+/// #[derive(Debug)]
+/// struct Point {
+///     x: i32,
+///     y: i32,
+/// }
+///
+/// fn main() {
+///     let value: Option<Result<Point, String>> = Some(Ok(Point { x: 0, y: 7 }));
+///     assert_matches_explain!(value, Some(Ok(Point { x: 0, y })) if y > 0);
+/// }
+/// ```
+#[macro_export]
+macro_rules! assert_matches_explain {
+    (@split $expression:tt [$($pattern:tt)*] if $($guard:tt)+) => {
+        $crate::assert_matches_explain!(@match $expression [$($pattern)*] [$($guard)+])
+    };
+    (@split $expression:tt [$($pattern:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::assert_matches_explain!(@split $expression [$($pattern)* $next] $($rest)*)
+    };
+    (@split $expression:tt [$($pattern:tt)*]) => {
+        $crate::assert_matches_explain!(@match $expression [$($pattern)*] [])
+    };
+    (@match ($expression:expr) [$($pattern:tt)*] []) => {
+        match $expression {
+            $($pattern)* => (),
+            #[allow(unreachable_patterns)]
+            ref value => {
+                $crate::assert_matches_explain!(@check [value, stringify!($($pattern)*), [] [] []] value, $($pattern)*);
+                $crate::assert_matches_explain!(@guard value, [$($pattern)*] [])
+            }
+        }
+    };
+    (@match ($expression:expr) [$($pattern:tt)*] [$($guard:tt)+]) => {
+        match $expression {
+            $($pattern)* if $($guard)+ => (),
+            ref value => {
+                $crate::assert_matches_explain!(
+                    @check [value, stringify!($($pattern)* if $($guard)+), [] [] []] value, $($pattern)*
+                );
+                $crate::assert_matches_explain!(@guard value, [$($pattern)*] [$($guard)+])
+            }
+        }
+    };
+    (@guard $value:ident, [$($pattern:tt)*] []) => {
+        panic!(
+            "assertion failed: `{}` does not match `{}`",
+            $crate::__private::Value($value),
+            stringify!($($pattern)*)
+        )
+    };
+    (@guard $value:ident, [$($pattern:tt)*] [$($guard:tt)+]) => {
+        panic!(
+            "assertion failed: `{}` does not match `{}`\n  matched `{}` but the guard `{}` was false",
+            $crate::__private::Value($value),
+            stringify!($($pattern)* if $($guard)+),
+            stringify!($($pattern)*),
+            stringify!($($guard)+)
+        )
+    };
+    (@fail [$top:ident, $whole:expr, [$($prefix:expr,)*] [$($suffix:expr,)*] []] $value:ident, $expected:expr) => {
+        panic!("assertion failed: `{}` does not match `{}`", $crate::__private::Value($top), $whole)
+    };
+    (@fail [$top:ident, $whole:expr, [$($prefix:expr,)*] [$($suffix:expr,)*] [$name:tt]] $value:ident, $expected:expr) => {
+        panic!(
+            "assertion failed: `{}` does not match `{}`\n  matched `{}` but field `{}` was `{}`, expected `{}`",
+            $crate::__private::Value($top),
+            $whole,
+            concat!($($prefix,)* "_" $(, $suffix)*),
+            stringify!($name),
+            $crate::__private::Value($value),
+            $expected
+        )
+    };
+    (@layer $ctx:tt $value:ident, ($scrutinee:expr) ($expected:expr) $($pattern:tt)+) => {{
+        #[allow(unused_variables, unreachable_patterns)]
+        let matched = match $scrutinee {
+            $($pattern)+ => true,
+            _ => false,
+        };
+        if !matched {
+            $crate::assert_matches_explain!(@fail $ctx $value, $expected)
+        }
+    }};
+    (@descend [$top:ident, $whole:expr, [$($prefix:expr,)*] [$($suffix:expr,)*] $parent:tt] [$($open:expr,)*] [$($close:expr,)*]
+        $name:tt $value:ident, $($pattern:tt)+) => {
+        $crate::assert_matches_explain!(
+            @check [$top, $whole, [$($prefix,)* $($open,)*] [$($close,)* $($suffix,)*] [$name]] $value, $($pattern)+
+        )
+    };
+    (@check $ctx:tt $value:ident, $first:ident $(:: $rest:ident)* ( $($fields:tt)* )) => {{
+        $crate::assert_matches_explain!(
+            @layer $ctx $value, ($value) (concat!(stringify!($first), $("::", stringify!($rest),)* stringify!(($($fields)*))))
+            $first $(:: $rest)* (..)
+        );
+        $crate::assert_matches_explain!(
+            @tuple $ctx $value, [$first $(:: $rest)*] [stringify!($first), $("::", stringify!($rest),)* "(",]
+            [0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15] [] [] $($fields)*
+        );
+    }};
+    (@check $ctx:tt $value:ident, $first:ident $(:: $rest:ident)* { $($fields:tt)* }) => {{
+        $crate::assert_matches_explain!(
+            @layer $ctx $value, ($value) (concat!(stringify!($first), $("::", stringify!($rest),)* " ", stringify!({ $($fields)* })))
+            $first $(:: $rest)* { .. }
+        );
+        $crate::assert_matches_explain!(
+            @struct $ctx $value, [$first $(:: $rest)*] [stringify!($first), $("::", stringify!($rest),)*] $($fields)*
+        );
+    }};
+    (@check $ctx:tt $value:ident, ( $($fields:tt)* )) => {
+        $crate::assert_matches_explain!(@tuple $ctx $value, [] ["(",] [0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15] [] [] $($fields)*)
+    };
+    (@check $ctx:tt $value:ident, $literal:literal) => {
+        $crate::assert_matches_explain!(@layer $ctx $value, (*$value) (stringify!($literal)) $literal)
+    };
+    (@check $ctx:tt $value:ident, $($pattern:tt)+) => {
+        $crate::assert_matches_explain!(@layer $ctx $value, ($value) (stringify!($($pattern)+)) $($pattern)+)
+    };
+    // A `..` rest pattern ends the fields that can be explained.
+    (@tuple $ctx:tt $value:ident, [$($path:tt)*] [$($open:expr,)*] $indices:tt [$($skip:tt)*] [..] $($rest:tt)*) => {};
+    // A parenthesized pattern, rather than a tuple.
+    (@tuple $ctx:tt $value:ident, [] [$($open:expr,)*] $indices:tt [] [$($current:tt)+]) => {
+        $crate::assert_matches_explain!(@check $ctx $value, $($current)+)
+    };
+    (@tuple $ctx:tt $value:ident, [$($path:tt)*] [$($open:expr,)*] [$index:tt $($indices:tt)*] [$($skip:tt)*] [$($current:tt)+]
+        , $($rest:tt)+) => {
+        $crate::assert_matches_explain!(@element $ctx $value, [$($path)*] [$($open,)*] $index [$($skip)*] [", ..)",] $($current)+);
+        $crate::assert_matches_explain!(
+            @tuple $ctx $value, [$($path)*] [$($open,)* "_, ",] [$($indices)*] [$($skip)* _,] [] $($rest)+
+        );
+    };
+    (@tuple $ctx:tt $value:ident, [$($path:tt)*] [$($open:expr,)*] [$index:tt $($indices:tt)*] [$($skip:tt)*] [$($current:tt)+]
+        $(,)?) => {
+        $crate::assert_matches_explain!(@element $ctx $value, [$($path)*] [$($open,)*] $index [$($skip)*] [")",] $($current)+);
+    };
+    (@tuple $ctx:tt $value:ident, [$($path:tt)*] [$($open:expr,)*] $indices:tt [$($skip:tt)*] [$($current:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::assert_matches_explain!(@tuple $ctx $value, [$($path)*] [$($open,)*] $indices [$($skip)*] [$($current)* $next] $($rest)*)
+    };
+    (@tuple $ctx:tt $value:ident, [$($path:tt)*] [$($open:expr,)*] $indices:tt [$($skip:tt)*] []) => {};
+    (@element $ctx:tt $value:ident, [$($path:tt)*] [$($open:expr,)*] $index:tt [$($skip:tt)*] [$($close:expr,)*] $($pattern:tt)+) => {
+        #[allow(unreachable_patterns)]
+        match $value {
+            $($path)* ($($skip)* field, ..) => {
+                $crate::assert_matches_explain!(@descend $ctx [$($open,)*] [$($close,)*] $index field, $($pattern)+)
+            }
+            _ => (),
+        }
+    };
+    (@struct $ctx:tt $value:ident, [$($path:tt)*] [$($open:expr,)*] .. $($rest:tt)*) => {};
+    (@struct $ctx:tt $value:ident, [$($path:tt)*] [$($open:expr,)*] $name:tt : $($rest:tt)*) => {
+        $crate::assert_matches_explain!(@field $ctx $value, [$($path)*] [$($open,)*] $name [] $($rest)*)
+    };
+    (@struct $ctx:tt $value:ident, [$($path:tt)*] [$($open:expr,)*] $($binding:tt)+) => {
+        $crate::assert_matches_explain!(@binding $ctx $value, [$($path)*] [$($open,)*] $($binding)+)
+    };
+    (@struct $ctx:tt $value:ident, [$($path:tt)*] [$($open:expr,)*]) => {};
+    // A shorthand field, such as `ref x`, always matches.
+    (@binding $ctx:tt $value:ident, [$($path:tt)*] [$($open:expr,)*] , $($rest:tt)*) => {
+        $crate::assert_matches_explain!(@struct $ctx $value, [$($path)*] [$($open,)*] $($rest)*)
+    };
+    (@binding $ctx:tt $value:ident, [$($path:tt)*] [$($open:expr,)*] $next:tt $($rest:tt)*) => {
+        $crate::assert_matches_explain!(@binding $ctx $value, [$($path)*] [$($open,)*] $($rest)*)
+    };
+    (@binding $ctx:tt $value:ident, [$($path:tt)*] [$($open:expr,)*]) => {};
+    (@field $ctx:tt $value:ident, [$($path:tt)*] [$($open:expr,)*] $name:tt [$($current:tt)+] $(, $($rest:tt)*)?) => {
+        #[allow(unreachable_patterns)]
+        match $value {
+            $($path)* { $name: field, .. } => $crate::assert_matches_explain!(
+                @descend $ctx [$($open,)* " { ", stringify!($name), ": ",] [", .. }",] $name field, $($current)+
+            ),
+            _ => (),
+        }
+        $crate::assert_matches_explain!(@struct $ctx $value, [$($path)*] [$($open,)*] $($($rest)*)?);
+    };
+    (@field $ctx:tt $value:ident, [$($path:tt)*] [$($open:expr,)*] $name:tt [$($current:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::assert_matches_explain!(@field $ctx $value, [$($path)*] [$($open,)*] $name [$($current)* $next] $($rest)*)
+    };
+    ($expression:expr, $($pattern:tt)+) => {
+        $crate::assert_matches_explain!(@split ($expression) [] $($pattern)+)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn matches_works() {
+        let foo = Some("-12");
+        assert!(matches!(foo, Some(bar) if
+            matches!(bar.as_bytes()[0], b'+' | b'-') &&
+            matches!(bar.as_bytes()[1], b'0'..=b'9')
+        ));
+    }
+
+    #[test]
+    fn assert_matches_works() {
+        let foo = Some("-12");
+        assert_matches!(foo, Some(bar) if
+            matches!(bar.as_bytes()[0], b'+' | b'-') &&
+            matches!(bar.as_bytes()[1], b'0'..=b'9')
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "claim_compat"))]
+    #[should_panic(expected = "assertion failed: `Some(\"-AB\")` does not match ")]
+    fn assert_matches_panics() {
+        let foo = Some("-AB");
+        assert_matches!(foo, Some(bar) if
+            matches!(bar.as_bytes()[0], b'+' | b'-') &&
+            matches!(bar.as_bytes()[1], b'0'..=b'9')
+        );
+    }
+
+    #[test]
+    fn unwrap_match_works() {
+        #[allow(dead_code)]
+        #[derive(Debug)]
+        enum Foo {
+            A(u32),
+            B(f32),
+        }
+
+        let foo = Foo::B(0.5);
+        let f = unwrap_match!(foo, Foo::B(f) => f);
+        assert_eq!(f, 0.5);
+    }
+
+    #[test]
+    #[should_panic(expected = "assertion failed: `B(0.5)` does not match `Foo::A(i) if i < 10`")]
+    fn unwrap_match_panics() {
+        #[allow(dead_code)]
+        #[derive(Debug)]
+        enum Foo {
+            A(u32),
+            B(f32),
+        }
+
+        let foo = Foo::B(0.5);
+        let _i = unwrap_match!(foo, Foo::A(i) if i < 10 => i);
+    }
+
+    #[test]
+    fn is_variant_works() {
+        #[allow(dead_code)]
+        enum Status {
+            Active,
+            Paused,
+            Stopped(i32),
+        }
+
+        is_variant!(Status, {
+            is_active => Active,
+            is_paused => Paused,
+            is_stopped => Stopped(_),
+        });
+
+        let active = Status::Active;
+        assert!(active.is_active());
+        assert!(!active.is_paused());
+        assert!(!active.is_stopped());
+
+        let paused = Status::Paused;
+        assert!(!paused.is_active());
+        assert!(paused.is_paused());
+        assert!(!paused.is_stopped());
+
+        let stopped = Status::Stopped(-1);
+        assert!(!stopped.is_active());
+        assert!(!stopped.is_paused());
+        assert!(stopped.is_stopped());
+    }
+
+    mod is_variant_paths {
+        pub mod shapes {
+            #[allow(dead_code)]
+            pub enum Tree<'a, T> {
+                Leaf(&'a T),
+                Node { children: Vec<Tree<'a, T>> },
+            }
+        }
+
+        is_variant!(impl<'a, T> self::shapes::Tree<'a, T>, {
+            is_leaf => Leaf(_),
+            is_node => Node { .. },
+        });
+
+        #[test]
+        fn generic_enum_behind_path() {
+            let leaf = shapes::Tree::Leaf(&1);
+            assert!(leaf.is_leaf());
+            assert!(!leaf.is_node());
+            assert!(shapes::Tree::Node { children: vec![leaf] }.is_node());
+        }
+    }
+
+    #[allow(dead_code)]
+    #[derive(Debug)]
+    struct Request {
+        method: &'static str,
+        status: u16,
+        retries: u8,
+        cached: bool,
+        body: &'static str,
+    }
+
+    #[test]
+    fn assert_matches_partial_works() {
+        let request = Request { method: "GET", status: 200, retries: 1, cached: false, body: "" };
+        assert_matches_partial!(request, Request { method: "GET", status: 200..=299, retries: 0..=3, cached: false, .. });
+        assert_matches_partial!((1, Some("two"), 3), (1, Some(_), 0..=5));
+    }
+
+    #[test]
+    #[should_panic(expected = "matched 3/4 specified constraints; failing: `status`")]
+    fn assert_matches_partial_panics() {
+        let request = Request { method: "GET", status: 404, retries: 1, cached: false, body: "Not Found" };
+        assert_matches_partial!(request, Request { method: "GET", status: 200..=299, retries: 0..=3, cached: false, .. });
+    }
+
+    #[test]
+    #[should_panic(expected = "matched 3/4 specified constraints; failing: `2`")]
+    fn assert_matches_partial_panics_on_tuple() {
+        #[derive(Debug)]
+        struct Row(u8, char, i32, bool);
+
+        assert_matches_partial!(Row(1, 'a', -3, true), Row(1, 'a'..='z', 0..=9, true));
+    }
+
+    #[test]
+    fn const_matcher_works() {
+        #[derive(Clone, Copy, Debug, PartialEq)]
+        enum Class {
+            Digit,
+            Alpha,
+            Space,
+            Other,
+        }
+
+        const_matcher!(#[inline] fn classify(b: u8) -> Class {
+            b'0'..=b'9' => Class::Digit,
+            b'a'..=b'z' | b'A'..=b'Z' => Class::Alpha,
+            b if b == b' ' || b == b'\t' => Class::Space,
+            _ => Class::Other,
+        });
+
+        const TABLE: [Class; 256] = {
+            let mut table = [Class::Other; 256];
+            let mut i = 0;
+            while i < 256 {
+                table[i] = classify(i as u8);
+                i += 1;
+            }
+            table
+        };
+        const DIGIT: Class = classify(b'5');
+
+        assert_eq!(DIGIT, Class::Digit);
+        assert_eq!(TABLE[b'x' as usize], Class::Alpha);
+        assert_eq!(TABLE[b'\t' as usize], Class::Space);
+        assert_eq!(TABLE[0xff], Class::Other);
+        for b in 0..=255u8 {
+            assert_eq!(TABLE[b as usize], classify(b));
+        }
+    }
+
+    #[test]
+    fn unless_match_works() {
+        enum Input {
+            Malformed,
+            Text(String),
+        }
+
+        fn process(input: Input) -> String {
+            match input {
+                Input::Text(s) => s.to_uppercase(),
+                Input::Malformed => unreachable!(),
+            }
+        }
+
+        let input = Input::Text("hello".to_owned());
+        let output = unless_match!(input, Input::Malformed => process(input), else String::new());
+        assert_eq!(output, "HELLO");
+
+        let input = Input::Malformed;
+        let output = unless_match!(input, Input::Malformed => process(input), else String::new());
+        assert_eq!(output, "");
+
+        let mut evaluated = 0;
+        let mut next = || {
+            evaluated += 1;
+            evaluated
+        };
+        assert_eq!(unless_match!(next(), 1 => "other", else "one"), "one");
+        assert_eq!(unless_match!(next(), n if n > 2 => "small", else "large"), "small");
+        assert_eq!(evaluated, 2);
+    }
+
+    #[test]
+    fn same_variant_works() {
+        #[allow(dead_code)]
+        #[derive(Debug)]
+        enum Event {
+            Key(char),
+            Click { x: i32, y: i32 },
+        }
+
+        assert!(same_variant!(Event::Key('a'), Event::Key('b')));
+        assert!(same_variant!(Event::Click { x: 0, y: 0 }, Event::Click { x: 1, y: 2 }));
+        assert!(!same_variant!(Event::Key('a'), Event::Click { x: 0, y: 0 }));
+        assert_same_variant!(Event::Key('a'), Event::Key('z'));
+    }
+
+    #[test]
+    #[should_panic(expected = "assertion failed: `Some(1)` is not the same variant as `None`")]
+    fn assert_same_variant_panics() {
+        assert_same_variant!(Some(1), None);
+    }
+
+    #[test]
+    #[deny(warnings)]
+    fn matches_accepts_arm_attributes() {
+        let foo = Some(3);
+        assert!(matches!(#[allow(unreachable_patterns)] foo, Some(_) | Some(3)));
+        assert!(!matches!(#[allow(unreachable_patterns)] None::<u8>, Some(_) | Some(3)));
+        assert!(matches!(#[allow(unreachable_patterns)] foo, Some(_) | None));
+        assert_matches!(#[allow(unreachable_patterns)] foo, Some(_) | Some(3));
+        assert_matches!(#[allow(unreachable_patterns)] foo, Some(_) | None, "unreachable");
+    }
+
+    #[test]
+    fn matches_any_of_works() {
+        #[allow(dead_code)]
+        enum Token {
+            Number(i64),
+            Ident(&'static str),
+            Pair(i64, &'static str),
+            Eof,
+        }
+
+        let mut checked = Vec::new();
+        let mut check = |i: usize| {
+            checked.push(i);
+            true
+        };
+        let token = Token::Ident("x");
+        assert!(matches_any_of!(token, [
+            Token::Number(n) if check(0) && n > 0,
+            Token::Ident(s) if check(1) && s.len() == 1,
+            Token::Pair(n, s) if check(2) && n as usize == s.len(),
+        ]));
+        assert_eq!(checked, [1]);
+
+        let strict = false;
+        assert!(!matches_any_of!(Token::Number(1), [Token::Number(_), Token::Pair(..)] if strict));
+        assert!(!matches_any_of!(Token::Eof, [Token::Number(_), Token::Ident(_), Token::Pair(..)]));
+    }
+
+    #[test]
+    fn try_unwrap_match_works() {
+        #[allow(dead_code)]
+        enum Foo {
+            A(u32),
             B(f32),
         }
 
-        let foo = Foo::B(0.5);
-        let f = unwrap_match!(foo, Foo::B(f) => f);
-        assert_eq!(f, 0.5);
+        assert_eq!(try_unwrap_match!(Foo::A(3), Foo::A(i) => i + 1), Ok(4));
+        assert_eq!(try_unwrap_match!(Foo::B(0.5), Foo::A(i) if i < 10 => i), Err("Foo::A(i) if i < 10"));
+        assert_eq!(try_unwrap_match!(Foo::B(0.5), Foo::B(f) => f), Ok(0.5));
+    }
+
+    #[test]
+    fn deref_matches_works() {
+        use std::borrow::Cow;
+
+        let borrowed: Cow<str> = Cow::Borrowed("expected");
+        let owned: Cow<str> = Cow::Owned("expected".to_owned());
+        let boxed: Box<str> = "unexpected".into();
+        assert!(deref_matches!(borrowed, "expected"));
+        assert!(deref_matches!(owned, "expected"));
+        assert!(!deref_matches!(boxed, "expected"));
+        assert!(deref_matches!(boxed, s if s.starts_with("un")));
+    }
+
+    #[test]
+    fn assert_matches_fmt_is_lazy() {
+        use std::cell::Cell;
+
+        let calls = Cell::new(0);
+        let compact = |value: &Option<Vec<u8>>| {
+            calls.set(calls.get() + 1);
+            format!("{} bytes", value.as_ref().map_or(0, Vec::len))
+        };
+        assert_matches!(Some(vec![1, 2, 3]), Some(_), @fmt compact);
+        assert_eq!(calls.get(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "assertion failed: `3 bytes` does not match `Some(ref v) if v.is_empty()`")]
+    fn assert_matches_fmt_panics() {
+        let compact = |value: &Option<Vec<u8>>| format!("{} bytes", value.as_ref().map_or(0, Vec::len));
+        assert_matches!(Some(vec![1, 2, 3]), Some(ref v) if v.is_empty(), @fmt compact);
+    }
+
+    #[test]
+    fn matches_evaluates_once() {
+        use std::cell::Cell;
+
+        let scrutinee_calls = Cell::new(0);
+        let guard_calls = Cell::new(0);
+        let next = |value: Option<i32>| {
+            scrutinee_calls.set(scrutinee_calls.get() + 1);
+            value
+        };
+        let guard = |n: i32| {
+            guard_calls.set(guard_calls.get() + 1);
+            n > 0
+        };
+
+        assert!(matches!(next(Some(1)), Some(n) if guard(n)));
+        assert_eq!((scrutinee_calls.get(), guard_calls.get()), (1, 1));
+
+        assert!(!matches!(next(Some(-1)), Some(n) if guard(n)));
+        assert_eq!((scrutinee_calls.get(), guard_calls.get()), (2, 2));
+
+        assert!(!matches!(next(None), Some(n) if guard(n)));
+        assert_eq!((scrutinee_calls.get(), guard_calls.get()), (3, 2));
+    }
+
+    #[test]
+    fn assert_matches_evaluates_once_on_failure() {
+        use std::cell::Cell;
+        use std::panic;
+
+        let calls = Cell::new(0);
+        let next = || {
+            calls.set(calls.get() + 1);
+            calls.get()
+        };
+        let result = panic::catch_unwind(panic::AssertUnwindSafe(|| assert_matches!(next(), 0)));
+        assert!(result.is_err());
+        let result = panic::catch_unwind(panic::AssertUnwindSafe(|| unwrap_match!(next(), 0 => ())));
+        assert!(result.is_err());
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[derive(Debug)]
+    enum Door {
+        Closed,
+        Open { width: u8 },
+    }
+
+    impl Door {
+        fn open(&mut self) {
+            *self = Door::Open { width: 90 };
+        }
+    }
+
+    #[test]
+    fn assert_transition_works() {
+        let mut door = Door::Closed;
+        assert_transition!(door, Door::Closed, Door::open, Door::Open { width } if width > 45);
+        assert_transition!(door, Door::Open { .. }, |door: &mut Door| *door = Door::Closed, Door::Closed);
+    }
+
+    #[test]
+    #[should_panic(expected = "assertion failed: state `Open { width: 90 }` does not match `Door::Closed` after the transition")]
+    fn assert_transition_panics_after() {
+        let mut door = Door::Closed;
+        assert_transition!(door, Door::Closed, Door::open, Door::Closed);
+    }
+
+    #[test]
+    #[should_panic(expected = "assertion failed: state `Closed` does not match `Door::Open { .. }` before the transition")]
+    fn assert_transition_panics_before() {
+        let mut door = Door::Closed;
+        assert_transition!(door, Door::Open { .. }, Door::open, Door::Open { .. });
+    }
+
+    #[test]
+    fn labeled_match_works() {
+        #[allow(dead_code)]
+        enum Event {
+            Connect { port: u16 },
+            Message(String),
+            Disconnect,
+            Unknown,
+        }
+
+        let classify = |event: &Event| labeled_match!(*event, {
+            "connect.privileged" => Event::Connect { port } if port < 1024,
+            "connect" => Event::Connect { .. },
+            "message" => Event::Message(_),
+            "disconnect" => Event::Disconnect,
+        });
+        assert_eq!(classify(&Event::Connect { port: 80 }), Some("connect.privileged"));
+        assert_eq!(classify(&Event::Connect { port: 8080 }), Some("connect"));
+        assert_eq!(classify(&Event::Message("hi".to_owned())), Some("message"));
+        assert_eq!(classify(&Event::Disconnect), Some("disconnect"));
+        assert_eq!(classify(&Event::Unknown), None);
+        assert_eq!(labeled_match!(true, { "yes" => true, "no" => false }), Some("yes"));
+    }
+
+    #[derive(Debug)]
+    enum Message {
+        Text(String),
+        Binary(Vec<u8>),
+    }
+
+    #[test]
+    fn unwrap_match_returns_borrow() {
+        let message = Message::Text("hello".to_owned());
+        let text: &str = unwrap_match!(message, Message::Text(ref s) => s);
+        let binary = Message::Binary(vec![1, 2]);
+        let first: &u8 = unwrap_match!(&binary, Message::Binary(b) => &b[0]);
+        assert_eq!(text, "hello");
+        assert_eq!(*first, 1);
+        assert_matches!(message, Message::Text(_));
+    }
+
+    #[test]
+    fn unwrap_match_returns_owned() {
+        let make = || Message::Binary(vec![1, 2]);
+        let bytes: Vec<u8> = unwrap_match!(make(), Message::Binary(b) => b);
+        let message = Message::Text("hello".to_owned());
+        let text: String = unwrap_match!(message, Message::Text(s) => s);
+        assert_eq!(bytes, [1, 2]);
+        assert_eq!(text, "hello");
+    }
+
+    fn panic_message<F: FnOnce() + std::panic::UnwindSafe>(f: F) -> String {
+        let payload = std::panic::catch_unwind(f).unwrap_err();
+        match payload.downcast::<String>() {
+            Ok(message) => *message,
+            Err(payload) => payload.downcast_ref::<&str>().unwrap().to_string(),
+        }
+    }
+
+    #[test]
+    fn assert_matches_truncated_works() {
+        let message = panic_message(|| assert_matches_truncated!(vec![1234; 1000], ref v if v.is_empty(), 20));
+        assert_eq!(message, "assertion failed: `[1234, 1234, 1234, 1...` does not match `ref v if v.is_empty()`");
+
+        let message = panic_message(|| assert_matches_truncated!(Some("é".repeat(100)), None, 8));
+        assert!(message.starts_with("assertion failed: `Some(\"éé...` "));
+
+        let message = panic_message(|| assert_matches_truncated!(Some(12), None, 8));
+        assert_eq!(message, "assertion failed: `Some(12)` does not match `None`");
+
+        assert_matches_truncated!(vec![1234; 1000], ref v if !v.is_empty(), 20);
+    }
+
+    #[test]
+    fn assert_matches_monotonic_works() {
+        assert_matches_monotonic!(vec![1, 2, 2, 5], (a, b) if a <= b);
+        assert_matches_monotonic!("abc".chars(), (a, b) if (*a as u8) + 1 == *b as u8);
+        assert_matches_monotonic!(Vec::<u8>::new(), (a, b) if a < b);
+        assert_matches_monotonic!(Some(1), (_, _) if false);
+    }
+
+    #[test]
+    #[should_panic(expected = "assertion failed: elements 2 and 3 (`7`, `4`) do not match `(a, b) if a <= b`")]
+    fn assert_matches_monotonic_panics() {
+        assert_matches_monotonic!([1, 3, 7, 4, 8].iter(), (a, b) if a <= b);
+    }
+
+    #[test]
+    #[cfg(feature = "claim_compat")]
+    fn assert_matches_claim_compat_message() {
+        let message = panic_message(|| assert_matches!(Some(4).map(|n| n * 2), None | Some(0)));
+        assert_eq!(
+            message,
+            "assertion failed: expression does not match pattern\n  expression: Some(4).map(|n| n * 2)\n      actual: Some(8)\n     pattern: None | Some(0)"
+        );
+    }
+
+    #[test]
+    fn generic_paths_work() {
+        #[allow(dead_code)]
+        #[derive(Debug)]
+        enum Foo<T> {
+            A,
+            B(T),
+        }
+
+        fn is_a<T>(foo: &Foo<T>) -> bool {
+            matches!(*foo, Foo::<T>::A)
+        }
+
+        assert!(is_a(&Foo::<()>::A));
+        assert!(!is_a(&Foo::B(1)));
+        assert!(matches!(Some(1u8), Option::<u8>::Some(1) | Option::<u8>::None));
+        assert_matches!(Some(1u8), Option::<u8>::Some(1));
+        assert_eq!(unwrap_match!(Foo::B(2), Foo::<i32>::B(n) => n), 2);
+        assert_eq!(option_match!(Ok::<u8, ()>(3), Result::<u8, ()>::Ok(n) => n), Some(3));
+        assert_eq!(try_unwrap_match!(Foo::B(2), Foo::<i32>::A => ()), Err("Foo::<i32>::A"));
+        assert_eq!(labeled_match!(Foo::B(2), { "a" => Foo::<i32>::A, "b" => Foo::<i32>::B(_) }), Some("b"));
+        assert!(matches_any_of!(Foo::B(2), [Foo::<i32>::A, Foo::<i32>::B(2)]));
+        assert_matches_partial!(Some(5u8), Option::<u8>::Some { 0: 1..=9 });
+    }
+
+    #[test]
+    #[should_panic(expected = "matched 0/1 specified constraints; failing: `0`")]
+    fn assert_matches_partial_panics_on_generic_path() {
+        assert_matches_partial!(Some(15u8), Option::<u8>::Some { 0: 1..=9 });
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn assert_matches_timed_works() {
+        let slow = || {
+            std::thread::sleep(std::time::Duration::from_millis(20));
+            Some(1)
+        };
+        assert_matches_timed!(slow(), Some(_));
+
+        let message = panic_message(|| assert_matches_timed!(slow(), None));
+        assert!(message.starts_with("assertion failed: `Some(1)` does not match `None` (scrutinee evaluated in "));
+        let elapsed = message.rsplit(' ').next().unwrap();
+        assert!(elapsed.ends_with("ms)"), "{}", message);
+        assert!(elapsed.trim_end_matches("ms)").parse::<f64>().unwrap() >= 20.0, "{}", message);
+    }
+
+    bitflags::bitflags! {
+        #[derive(Clone, Copy, Debug, PartialEq)]
+        struct Permissions: u8 {
+            const READ = 0b001;
+            const WRITE = 0b010;
+            const EXECUTE = 0b100;
+        }
+    }
+
+    #[test]
+    fn flags_match_works() {
+        let permissions = Permissions::READ | Permissions::WRITE;
+        assert!(flags_match!(permissions, Permissions::READ));
+        assert!(flags_match!(permissions, Permissions::READ | Permissions::WRITE));
+        assert!(!flags_match!(permissions, Permissions::READ | Permissions::EXECUTE));
+        assert_flags_match!(permissions, Permissions::WRITE);
+        assert_flags_match!(permissions, Permissions::READ | Permissions::WRITE, "custom message");
+    }
+
+    #[test]
+    #[should_panic(expected = "assertion failed: `Permissions(READ | WRITE)` does not contain `Permissions(WRITE | EXECUTE)` (missing `Permissions(EXECUTE)`)")]
+    fn assert_flags_match_panics() {
+        assert_flags_match!(Permissions::READ | Permissions::WRITE, Permissions::WRITE | Permissions::EXECUTE);
+    }
+
+    #[test]
+    fn scan_matches_works() {
+        #[allow(dead_code)]
+        enum Item {
+            Number(i32),
+            Word(&'static str),
+            Nothing,
+        }
+
+        let items = [Item::Number(3), Item::Word("a"), Item::Nothing, Item::Number(-1), Item::Word("bc")];
+        let sum = scan_matches!(items.iter(), &Item::Number(n) => n, 100, |sum, n| sum + n);
+        assert_eq!(sum, 102);
+
+        let words = scan_matches!(items.iter(), &Item::Word(w) if !w.is_empty() => w, String::new(), |mut s, w| {
+            s.push_str(w);
+            s.push(';');
+            s
+        });
+        assert_eq!(words, "a;bc;");
+
+        let none = scan_matches!(items.iter(), &Item::Number(n) if n > 10 => n, 7, |acc, n| acc * n);
+        assert_eq!(none, 7);
+    }
+
+    #[test]
+    #[deny(warnings)]
+    fn unwrap_match_allows_exhaustive_patterns() {
+        #[derive(Debug)]
+        enum Either {
+            Left(u8),
+            Right(u8),
+        }
+
+        let value = |either: Either| unwrap_match!(either, Either::Left(n) | Either::Right(n) => n);
+        assert_eq!(value(Either::Left(1)), 1);
+        assert_eq!(value(Either::Right(2)), 2);
+        assert_eq!(unwrap_match!(Either::Right(3), Either::Left(n) | Either::Right(n) => n, "unreachable"), 3);
+        assert_eq!(option_match!(Either::Left(4), Either::Left(n) | Either::Right(n) => n), Some(4));
+        assert_eq!(try_unwrap_match!(Either::Left(5), Either::Left(n) | Either::Right(n) => n), Ok(5));
+    }
+
+    #[test]
+    #[should_panic(expected = "assertion failed: `Err(\"no\")` does not match `Ok(n) | Err(n) if n == \"yes\"`")]
+    fn unwrap_match_panics_on_partial_alternation() {
+        let result: Result<&str, &str> = Err("no");
+        let _n = unwrap_match!(result, Ok(n) | Err(n) if n == "yes" => n);
+    }
+
+    #[test]
+    fn discriminant_match_works() {
+        #[allow(dead_code)]
+        enum Frame {
+            Ping,
+            Data(Vec<u8>),
+            Close(u16),
+            Reserved,
+        }
+
+        let tag = |frame: &Frame| -> u8 {
+            discriminant_match!(*frame, {
+                Frame::Ping => 0x9,
+                Frame::Data(ref data) if data.len() > 125 => 0x2,
+                Frame::Data(_) => 0x1,
+                Frame::Close(_) => 0x8,
+            }, 0xff)
+        };
+        assert_eq!(tag(&Frame::Ping), 0x9);
+        assert_eq!(tag(&Frame::Data(vec![0; 10])), 0x1);
+        assert_eq!(tag(&Frame::Data(vec![0; 200])), 0x2);
+        assert_eq!(tag(&Frame::Close(1000)), 0x8);
+        assert_eq!(tag(&Frame::Reserved), 0xff);
+
+        let tag: u8 = discriminant_match!(Frame::Reserved, { Frame::Ping => 1 });
+        assert_eq!(tag, 0);
+    }
+
+    #[test]
+    fn assert_any_predicate_works() {
+        let calls = std::cell::Cell::new(0);
+        let is_empty = |s: &&str| {
+            calls.set(calls.get() + 1);
+            s.is_empty()
+        };
+        let is_lowercase = |s: &&str| {
+            calls.set(calls.get() + 1);
+            s.chars().all(char::is_lowercase)
+        };
+        assert_any_predicate!("abc", [is_empty, is_lowercase], "not a word");
+        assert_eq!(calls.get(), 2);
+        assert_any_predicate!("", [is_empty, is_lowercase]);
+        assert_eq!(calls.get(), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "assertion failed: `\"ABC\"` does not satisfy any of the predicates: expected 2 predicates")]
+    fn assert_any_predicate_panics() {
+        let predicates: Vec<fn(&&str) -> bool> = vec![|s| s.is_empty(), |s| s.starts_with('a')];
+        assert_any_predicate!("ABC", [predicates[0], predicates[1]], "expected {} predicates", predicates.len());
+    }
+
+    #[test]
+    fn and_then_match_works() {
+        #[allow(dead_code)]
+        #[derive(Debug, PartialEq)]
+        enum Token {
+            Number(i64),
+            Word(&'static str),
+        }
+
+        let lex = |input: &'static str| -> Result<Token, String> {
+            if input.is_empty() {
+                Err("empty input".to_owned())
+            } else {
+                Ok(input.parse().map(Token::Number).unwrap_or(Token::Word(input)))
+            }
+        };
+        let number = |input| and_then_match!(lex(input), Token::Number(n) if n >= 0 => n as u64, else format!("{:?} is not a natural number", input));
+        assert_eq!(number("42"), Ok(42));
+        assert_eq!(number("-1"), Err("\"-1\" is not a natural number".to_owned()));
+        assert_eq!(number("abc"), Err("\"abc\" is not a natural number".to_owned()));
+        assert_eq!(number(""), Err("empty input".to_owned()));
+    }
+
+    #[test]
+    fn const_assert_matches_works() {
+        #[allow(dead_code)]
+        enum Mode {
+            Debug,
+            Release { lto: bool },
+        }
+
+        const MODE: Mode = Mode::Release { lto: true };
+        const LIMITS: [u32; 2] = [16, 1024];
+
+        const_assert_matches!(MODE, Mode::Release { lto: true });
+        const_assert_matches!(LIMITS, [min, max] if min < max);
+        const_assert_matches!(LIMITS[0], 1..=64);
+    }
+
+    #[test]
+    fn match_into_works() {
+        let lines = ["key=value", "# comment", "other=thing", ""];
+        let mut buffer = String::with_capacity(64);
+        let mut updates = Vec::new();
+        for line in &lines {
+            let updated = match_into!(line.split_once('='), Some((key, _)) if !key.is_empty() => key.to_uppercase(), &mut buffer);
+            updates.push((updated, buffer.clone()));
+        }
+        assert_eq!(updates, [
+            (true, "KEY".to_owned()),
+            (false, "KEY".to_owned()),
+            (true, "OTHER".to_owned()),
+            (false, "OTHER".to_owned()),
+        ]);
+    }
+
+    #[test]
+    fn windows_matches_works() {
+        let packet = b"\x00\x01GET /\r\n\r\n";
+        assert!(windows_matches!(packet, 4, [b'\r', b'\n', b'\r', b'\n']));
+        assert!(windows_matches!(&packet[..], 3, b"GET"));
+        assert!(!windows_matches!(packet, 3, b"PUT"));
+        assert!(windows_matches!(packet, 2, [a, b] if *a == 0 && *b == 1));
+        assert!(!windows_matches!(packet, packet.len() + 1, [_, ..]));
+        assert!(!windows_matches!(Vec::<u8>::new(), 1, [_]));
+    }
+
+    #[test]
+    fn question_match_works() {
+        fn parse_header(line: &str) -> Option<(&str, u32)> {
+            let (name, value) = question_match!(line.split_once(':'), Some((name, value)) if !name.is_empty() => (name, value));
+            let value = question_match!(value.trim().parse::<u32>(), Ok(value) => value);
+            Some((name, value))
+        }
+
+        assert_eq!(parse_header("Content-Length: 42"), Some(("Content-Length", 42)));
+        assert_eq!(parse_header("Content-Length 42"), None);
+        assert_eq!(parse_header(": 42"), None);
+        assert_eq!(parse_header("Content-Length: many"), None);
+    }
+
+    fn classify(coverage: &mut crate::MatchCoverage, input: Option<i32>) -> &'static str {
+        cov_matches!(coverage, input, {
+            None => "none",
+            Some(0) => "zero",
+            Some(n) if n < 0 => "negative",
+            Some(_) => "positive",
+        })
+    }
+
+    #[test]
+    fn cov_matches_works() {
+        let mut coverage = crate::MatchCoverage::new();
+        assert_eq!(classify(&mut coverage, Some(-4)), "negative");
+        assert_eq!(classify(&mut coverage, None), "none");
+        assert_eq!(classify(&mut coverage, Some(7)), "positive");
+        assert_eq!(classify(&mut coverage, Some(0)), "zero");
+        assert!((0..4).all(|arm| coverage.is_hit(arm)));
+        coverage.assert_all_hit();
+    }
+
+    #[test]
+    #[should_panic(expected = "assertion failed: match arms never hit: 1, 2")]
+    fn cov_matches_panics() {
+        let mut coverage = crate::MatchCoverage::new();
+        for input in &[None, Some(3), Some(12)] {
+            classify(&mut coverage, *input);
+        }
+        coverage.assert_all_hit();
+    }
+
+    #[cfg(if_let_guards)]
+    #[test]
+    fn if_let_guards_work() {
+        let input = Some("42");
+        assert!(matches!(input, Some(s) if let Ok(_) = s.parse::<i32>()));
+        assert!(!matches!(Some("x"), Some(s) if let Ok(_) = s.parse::<i32>()));
+        assert_matches!(input, Some(s) if let Ok(_) = s.parse::<i32>());
+        assert_matches!(input, Some(s) if let Ok(_) = s.parse::<i32>(), "not a number");
+        debug_assert_matches!(input, Some(s) if let Ok(_) = s.parse::<i32>());
+        assert_eq!(unwrap_match!(input, Some(s) if let Ok(n) = s.parse::<i32>() => n + 1), 43);
+        assert_eq!(unwrap_match!(input, Some(s) if let Ok(n) = s.parse::<i32>() => n, "not a number"), 42);
+        assert_eq!(option_match!(input, Some(s) if let Ok(n) = s.parse::<i32>() => n), Some(42));
+        assert_eq!(option_match!(Some("x"), Some(s) if let Ok(n) = s.parse::<i32>() => n), None);
+        let parsed = option_match!(Some(String::from(" 42 ")), Some(x) if let Ok(n) = x.trim().parse::<u8>() => n);
+        assert_eq!(parsed, Some(42));
+    }
+
+    #[cfg(if_let_guards)]
+    #[test]
+    #[should_panic(expected = "assertion failed: `Some(\"x\")` does not match `Some(s) if let Ok(n) = s.parse::<i32>()`")]
+    fn if_let_guards_panic() {
+        unwrap_match!(Some("x"), Some(s) if let Ok(n) = s.parse::<i32>() => n);
+    }
+
+    #[test]
+    fn clone_match_works() {
+        #[derive(Debug, PartialEq)]
+        enum Record {
+            User { name: String, tags: Vec<String> },
+            Anonymous,
+        }
+
+        let record = Record::User { name: "alice".to_owned(), tags: vec!["admin".to_owned()] };
+        let snapshot = clone_match!(&record, Record::User { name, tags } if !tags.is_empty() => (name.clone(), tags.clone()));
+        assert_eq!(snapshot, Some(("alice".to_owned(), vec!["admin".to_owned()])));
+        assert_eq!(record, Record::User { name: "alice".to_owned(), tags: vec!["admin".to_owned()] });
+        assert_eq!(clone_match!(&Record::Anonymous, Record::User { name, .. } => name.clone()), None);
+    }
+
+    #[allow(dead_code)]
+    #[derive(Debug)]
+    enum Token {
+        Eof,
+        Number(u32),
+        Ident { name: &'static str },
+    }
+
+    #[test]
+    fn assert_variant_works() {
+        assert_variant!(Token::Eof, Token::Eof);
+        assert_variant!(Token::Number(1), Token::Number);
+        assert_variant!(Token::Ident { name: "x" }, Token::Ident);
+    }
+
+    #[test]
+    #[should_panic(expected = "assertion failed: expected variant `Token::Number`, got `Token::Ident`")]
+    fn assert_variant_panics() {
+        assert_variant!(Token::Ident { name: "x" }, Token::Number);
+    }
+
+    #[test]
+    fn assert_variant_names_actual_variant() {
+        assert_eq!(
+            panic_message(|| assert_variant!(Token::Number(7), Token::Eof)),
+            "assertion failed: expected variant `Token::Eof`, got `Token::Number`"
+        );
+        assert_eq!(
+            panic_message(|| assert_variant!(Token::Eof, Token::Ident)),
+            "assertion failed: expected variant `Token::Ident`, got `Token::Eof`"
+        );
+    }
+
+    #[test]
+    fn starts_with_match_works() {
+        assert!(starts_with_match!("Hello", [b'H', b'e' | b'a']));
+        #[cfg(feature = "std")]
+        assert!(starts_with_match!(String::from("Hi"), [b'H', b'i']));
+        #[cfg(feature = "std")]
+        assert!(starts_with_match!(vec![1, 2, 3], [1, _]));
+        assert!(starts_with_match!(&[1, 2][..], []));
+    }
+
+    #[test]
+    fn starts_with_match_rejects_short_or_different() {
+        assert!(!starts_with_match!("H", [b'H', b'i']));
+        assert!(!starts_with_match!("", [b'H']));
+        assert!(!starts_with_match!("Yo", [b'H', b'i']));
+        assert!(!starts_with_match!([3, 2, 1], [1]));
+    }
+
+    #[test]
+    fn filter_matches_works() {
+        let items = vec![Ok(1), Err("bad"), Ok(12), Ok(3)];
+        let by_macro: Vec<_> = filter_matches!(items.clone(), Ok(n) if n < 10 => n).collect();
+        let by_adaptor: Vec<_> = crate::filter_match_fn(items, |item| option_match!(item, Ok(n) if n < 10 => n)).collect();
+        assert_eq!(by_macro, [1, 3]);
+        assert_eq!(by_macro, by_adaptor);
+    }
+
+    #[test]
+    fn match_filter_is_generic() {
+        fn errors<I: IntoIterator<Item = Result<u8, &'static str>>>(iter: I) -> usize {
+            crate::filter_match_fn(iter, |item| item.err()).count()
+        }
+
+        assert_eq!(errors(vec![Ok(1), Err("a"), Err("b")]), 2);
+        assert_eq!(filter_matches!(0..10, n if n % 3 == 0 => n).size_hint(), (0, Some(10)));
+    }
+
+    #[test]
+    fn assert_exactly_one_matches_works() {
+        assert_exactly_one_matches!(Some(3), [None, Some(0), Some(n) if n > 0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "to match `Some(-1)`, but 0 matched")]
+    fn assert_exactly_one_matches_panics_on_zero() {
+        assert_exactly_one_matches!(Some(-1), [None, Some(0), Some(n) if n > 0]);
+    }
+
+    #[test]
+    fn assert_exactly_one_matches_panics_on_overlap() {
+        assert_eq!(
+            panic_message(|| assert_exactly_one_matches!(15, [0..=10, 5..=20, 12 | 15])),
+            "assertion failed: expected exactly one pattern of `[0..=10, 5..=20, 12 | 15]` to match `15`, but 2 matched"
+        );
+    }
+
+    #[test]
+    fn order_match_works() {
+        #[derive(Debug, PartialEq)]
+        enum Task {
+            Idle,
+            Urgent(u32),
+            Normal(u32),
+        }
+
+        let mut tasks = vec![Task::Normal(2), Task::Idle, Task::Urgent(5), Task::Normal(1), Task::Urgent(3)];
+        tasks.sort_by_key(|task| order_match!(*task, {
+            Task::Urgent(n) => (0, n),
+            Task::Normal(n) if n < 2 => (1, n),
+            Task::Normal(n) => (2, n),
+            Task::Idle => (3, 0),
+        }));
+        assert_eq!(tasks, [Task::Urgent(3), Task::Urgent(5), Task::Normal(1), Task::Normal(2), Task::Idle]);
+    }
+
+    #[test]
+    fn assert_none_match_works() {
+        let results: Vec<Result<u8, &str>> = vec![Ok(1), Ok(2), Ok(3)];
+        assert_none_match!(results.clone(), Err(_));
+        assert_none_match!(results.iter(), Ok(n) if *n > 3);
+        assert_none_match!(Vec::<Result<u8, &str>>::new(), Err(_));
+    }
+
+    #[test]
+    #[should_panic(expected = "assertion failed: element 2 (`Err(\"timeout\")`) unexpectedly matched `Err(_)`")]
+    fn assert_none_match_panics() {
+        assert_none_match!(vec![Ok(1), Ok(2), Err("timeout"), Err("refused")], Err(_));
+    }
+
+    mod tuple_patterns {
+        type Triple = (Option<i32>, Option<&'static str>, u8);
+
+        const HIT: Triple = (Some(4), None, 3);
+        const MISS: Triple = (Some(4), Some("x"), 3);
+
+        #[test]
+        fn matches_works() {
+            assert!(matches!(HIT, (Some(x), None, 3) if x % 2 == 0));
+            assert!(!matches!(HIT, (Some(x), None, 3) if x > 10));
+            assert!(!matches!(MISS, (Some(_), None, 3)));
+            assert!(matches!(MISS, (Some(_), None, 3) | (Some(_), Some(_), 0..=3)));
+        }
+
+        #[test]
+        fn option_match_works() {
+            assert_eq!(option_match!(HIT, (Some(x), None, n) if x > 0 => x + n as i32), Some(7));
+            assert_eq!(option_match!(MISS, (Some(x), None, 3) => x), None);
+            assert_eq!(option_match!(MISS, (Some(x), None, _) | (Some(x), Some(_), _) if x == 4 => x), Some(4));
+        }
+
+        #[test]
+        fn unwrap_match_works() {
+            assert_eq!(unwrap_match!(HIT, (Some(x), None, 3) if x < 5 => x * 2), 8);
+            assert_eq!(unwrap_match!(MISS, (_, Some(s), _) => s), "x");
+        }
+
+        #[test]
+        #[should_panic(expected = "(Some(x), None, 3) if x > 5")]
+        fn unwrap_match_panics() {
+            unwrap_match!(HIT, (Some(x), None, 3) if x > 5 => x);
+        }
+
+        #[test]
+        fn assert_matches_works() {
+            assert_matches!(HIT, (Some(x), None, 3) if x == 4);
+            assert_matches!(MISS, (Some(4), Some("x" | "y"), _));
+        }
+
+        #[test]
+        #[cfg(not(feature = "claim_compat"))]
+        #[should_panic(expected = "assertion failed: `(Some(4), Some(\"x\"), 3)` does not match `(Some(x), None, 3) if x == 4`")]
+        fn assert_matches_panics() {
+            assert_matches!(MISS, (Some(x), None, 3) if x == 4);
+        }
+    }
+
+    #[test]
+    fn soft_assert_matches_works() {
+        assert_eq!(soft_assert_matches!(Some(3), Some(n) if n > 0 => n * 2, 0), 6);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "assertion failed: `Some(-3)` does not match `Some(n) if n > 0`")]
+    fn soft_assert_matches_panics_with_debug_assertions() {
+        soft_assert_matches!(Some(-3), Some(n) if n > 0 => n * 2, 0);
+    }
+
+    #[test]
+    #[cfg(not(debug_assertions))]
+    fn soft_assert_matches_defaults_without_debug_assertions() {
+        assert_eq!(soft_assert_matches!(Some(-3), Some(n) if n > 0 => n * 2, 0), 0);
+        assert_eq!(soft_assert_matches!(None::<i32>, Some(n) => n, -1), -1);
+    }
+
+    #[test]
+    fn is_none_and_is_some_work() {
+        let nested: Option<Option<u8>> = Some(None);
+        assert!(is_some!(nested));
+        assert!(is_none!(nested.flatten()));
+        assert!(is_none!(unwrap_match!(nested, Some(inner) => inner)));
+        assert!(!is_some!(None::<u8>));
+        assert!(!is_none!(Some(1)));
+    }
+
+    #[test]
+    fn is_ok_and_is_err_work() {
+        let nested: Result<Result<u8, &str>, &str> = Ok(Err("inner"));
+        assert!(is_ok!(nested));
+        assert!(is_err!(nested.and_then(|inner| inner)));
+        assert!(is_err!(option_match!(nested, Ok(inner) => inner).unwrap()));
+        assert!(!is_ok!(Err::<u8, _>("outer")));
+        assert!(!is_err!(Ok::<_, ()>(1)));
+    }
+
+    #[test]
+    #[cfg(not(feature = "claim_compat"))]
+    fn assert_matches_capture_works() {
+        let req_id = 7;
+        let attempt = "second";
+        assert_matches!(Ok::<u8, ()>(1), Ok(_), capture: [req_id, attempt]);
+        assert_eq!(
+            panic_message(|| assert_matches!(Err::<u8, _>("timeout"), Ok(_), capture: [req_id, attempt])),
+            "assertion failed: `Err(\"timeout\")` does not match `Ok(_)`, req_id = 7, attempt = \"second\""
+        );
+    }
+
+    #[test]
+    fn assert_matches_capture_formats_only_on_failure() {
+        struct Counted<'a>(&'a std::cell::Cell<u32>);
+
+        impl<'a> std::fmt::Debug for Counted<'a> {
+            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                self.0.set(self.0.get() + 1);
+                f.write_str("Counted")
+            }
+        }
+
+        let count = std::cell::Cell::new(0);
+        let counted = Counted(&count);
+        assert_matches!(Some(1), Some(_), capture: [counted]);
+        assert_eq!(count.get(), 0);
+        let message = panic_message(std::panic::AssertUnwindSafe(|| assert_matches!(None::<u8>, Some(_), capture: [counted])));
+        assert!(message.ends_with(", counted = Counted"));
+        assert_eq!(count.get(), 1);
+    }
+
+    #[test]
+    fn match_assertion_works() {
+        let assertion = crate::MatchAssertion::new(Some(4))
+            .matches(|v| matches!(*v, Some(1..=9)))
+            .and_then(|v| matches!(*v, Some(n) if n % 2 == 0));
+        assert!(assertion.passed());
+        assert_eq!(assertion.assert("even"), Some(4));
+    }
+
+    #[test]
+    fn match_assertion_panics() {
+        assert_eq!(
+            panic_message(|| {
+                crate::MatchAssertion::new(Some(3))
+                    .matches(|v| matches!(*v, Some(1..=9)))
+                    .and_then(|v| matches!(*v, Some(n) if n % 2 == 0))
+                    .and_then(|_| unreachable!())
+                    .assert("expected an even number");
+            }),
+            "assertion failed: expected an even number: check 2 of 3 failed for `Some(3)`"
+        );
+    }
+
+    mod raw_identifiers {
+        use super::panic_message;
+
+        #[allow(non_camel_case_types, dead_code)]
+        #[derive(Debug)]
+        enum Kind {
+            r#type(u8),
+            r#match { r#ref: bool },
+            Other,
+        }
+
+        #[test]
+        fn raw_identifiers_work_in_patterns() {
+            assert!(matches!(Kind::r#type(1), Kind::r#type(1)));
+            assert_eq!(option_match!(Kind::r#type(2), Kind::r#type(n) => n), Some(2));
+            assert!(unwrap_match!(Kind::r#match { r#ref: true }, Kind::r#match { r#ref } => r#ref));
+            assert_matches!(Kind::r#match { r#ref: false }, Kind::r#match { r#ref: false });
+            assert_variant!(Kind::r#type(3), Kind::r#type);
+        }
+
+        #[test]
+        #[cfg(not(feature = "claim_compat"))]
+        fn raw_identifiers_are_kept_in_pattern_messages() {
+            assert_eq!(
+                panic_message(|| assert_matches!(Kind::Other, Kind::r#type(_))),
+                "assertion failed: `Other` does not match `Kind::r#type(_)`"
+            );
+            assert_eq!(
+                panic_message(|| {
+                    unwrap_match!(Kind::Other, Kind::r#match { r#ref } => r#ref);
+                }),
+                "assertion failed: `Other` does not match `Kind::r#match { r#ref }`"
+            );
+        }
+
+        #[test]
+        fn raw_identifiers_are_stripped_from_variant_names() {
+            assert_eq!(
+                panic_message(|| assert_variant!(Kind::r#type(1), Kind::r#match)),
+                "assertion failed: expected variant `Kind::match`, got `Kind::type`"
+            );
+            assert_eq!(
+                panic_message(|| assert_variant!(Kind::Other, self::Kind::r#type)),
+                "assertion failed: expected variant `self::Kind::type`, got `self::Kind::Other`"
+            );
+        }
+    }
+
+    #[test]
+    fn split_match_works() {
+        fn parse_header(input: &[u8]) -> Option<(u8, &[u8])> {
+            split_match!(input, [0xCA, 0xFE, version @ 1..=3] => *version)
+        }
+
+        let input = vec![0xCA, 0xFE, 2, 10, 20];
+        assert_eq!(parse_header(&input), Some((2, &[10, 20][..])));
+        assert_eq!(split_match!(&input, [_, _] => ()), Some(((), &[2, 10, 20][..])));
+        assert_eq!(parse_header(&[0xCA, 0xFE, 3]), Some((3, &[][..])));
+    }
+
+    #[test]
+    fn split_match_rejects_short_or_different() {
+        assert_eq!(split_match!(&[0xCA][..], [0xCA, 0xFE] => ()), None);
+        assert_eq!(split_match!(&[0xCA, 0xFF, 1][..], [0xCA, 0xFE] => ()), None);
+    }
+
+    #[allow(dead_code)]
+    #[derive(Debug)]
+    struct Record {
+        id: u32,
+        name: &'static str,
+        created: u64,
+    }
+
+    #[test]
+    fn assert_matches_ignoring_works() {
+        assert_matches_ignoring!(Record { id: 1, name: "a", created: 10 }, Record { id: 1, name: "a", .. });
+        assert_matches_ignoring!(Record { id: 1, name: "a", created: 99 }, Record { id: 1, name: "a", .. });
+    }
+
+    #[test]
+    fn assert_matches_ignoring_panics() {
+        let message = panic_message(|| assert_matches_ignoring!(Record { id: 2, name: "b", created: 99 }, Record { id: 1..=9, name: "a", .. }));
+        assert!(message.starts_with("assertion failed: `Record { id: 2, name: \"b\", created: 99 }` does not match `Record {"));
+        assert!(message.ends_with(": mismatched fields: `name`"));
+    }
+
+    #[cfg(feature = "proptest")]
+    mod prop_assert_matches {
+        use proptest::test_runner::{Config, TestCaseError, TestError, TestRunner};
+
+        fn check(n: u32) -> Result<(), TestCaseError> {
+            prop_assert_matches!(n.checked_mul(3), Some(m) if m < 30)?;
+            Ok(())
+        }
+
+        #[test]
+        fn prop_assert_matches_works() {
+            assert_matches!(check(9), Ok(()));
+            assert_matches!(check(10), Err(TestCaseError::Fail(_)));
+        }
+
+        #[test]
+        fn prop_assert_matches_shrinks() {
+            let mut runner = TestRunner::new(Config { failure_persistence: None, ..Config::default() });
+            let result = runner.run(&(0u32..1000), check);
+            assert_matches!(result, Err(TestError::Fail(ref reason, 10)) if reason.message().contains("Some(30)"));
+        }
+
+        #[test]
+        #[cfg(not(feature = "claim_compat"))]
+        fn prop_assert_matches_reports_mismatch() {
+            assert_matches!(
+                check(20),
+                Err(TestCaseError::Fail(ref reason))
+                    if reason.message() == "assertion failed: `Some(60)` does not match `Some(m) if m < 30`"
+            );
+        }
+    }
+
+    #[test]
+    fn assert_match_count_works() {
+        let results: Vec<Result<u8, &str>> = vec![Ok(1), Err("a"), Ok(2), Ok(30)];
+        assert_match_count!(&results, Ok(_), 2..=4);
+        assert_match_count!(results.iter(), Ok(n) if *n < 10, 2..3);
+        assert_match_count!(results, Err(_), ..=1);
+    }
+
+    #[test]
+    #[should_panic(expected = "assertion failed: expected `2..=4` elements to match `Ok(_)`, but 1 matched")]
+    fn assert_match_count_panics_below() {
+        assert_match_count!(vec![Ok(1), Err(())], Ok(_), 2..=4);
+    }
+
+    #[test]
+    #[should_panic(expected = "assertion failed: expected `..2` elements to match `Some(n) if n > 0`, but 3 matched")]
+    fn assert_match_count_panics_above() {
+        assert_match_count!(vec![Some(1), Some(2), None, Some(3)], Some(n) if n > 0, ..2);
+    }
+
+    #[cfg(feature = "metrics")]
+    mod counted_match {
+        use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+
+        #[test]
+        fn counted_match_works() {
+            let recorder = DebuggingRecorder::new();
+            let snapshotter = recorder.snapshotter();
+            metrics::with_local_recorder(&recorder, || {
+                assert!(counted_match!("parsed", "7".parse::<u8>(), Ok(n) if n < 10));
+                assert!(!counted_match!("parsed", "x".parse::<u8>(), Ok(_)));
+                assert!(counted_match!("parsed", "8".parse::<u8>(), Ok(_)));
+                assert!(counted_match!("empty", None::<u8>, None));
+            });
+            let mut counters: Vec<_> = snapshotter
+                .snapshot()
+                .into_vec()
+                .into_iter()
+                .map(|(key, _, _, value)| {
+                    let key = key.key();
+                    let outcome = key.labels().find(|label| label.key() == "outcome").unwrap().value().to_owned();
+                    (key.name().to_owned(), outcome, value)
+                })
+                .collect();
+            counters.sort_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1)));
+            assert_eq!(
+                counters,
+                [
+                    ("empty".to_owned(), "match".to_owned(), DebugValue::Counter(1)),
+                    ("parsed".to_owned(), "match".to_owned(), DebugValue::Counter(2)),
+                    ("parsed".to_owned(), "mismatch".to_owned(), DebugValue::Counter(1)),
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn unwrap_match_deref_works() {
+        #[derive(Debug, PartialEq)]
+        struct Node {
+            value: u32,
+        }
+
+        let boxed: Option<Box<Node>> = Some(Box::new(Node { value: 1 }));
+        let node: &Node = unwrap_match_deref!(&boxed, Some(node) => node);
+        assert_eq!(node.value, 1);
+        assert_eq!(boxed, Some(Box::new(Node { value: 1 })));
+
+        let shared: Result<std::rc::Rc<Node>, ()> = Ok(std::rc::Rc::new(Node { value: 2 }));
+        let value = unwrap_match_deref!(shared, Ok(Node { value }) if *value > 1 => value);
+        assert_eq!(*value, 2);
+        assert_eq!(std::rc::Rc::strong_count(shared.as_ref().unwrap()), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "assertion failed: `None` does not match `Some(name)`")]
+    fn unwrap_match_deref_panics() {
+        let name: Option<Box<str>> = None;
+        unwrap_match_deref!(name, Some(name) => name);
+    }
+
+    #[cfg(feature = "std")]
+    mod shape {
+        use crate::{field_divergence, Shape};
+
+        #[derive(Debug)]
+        struct Payload {
+            kind: &'static str,
+            bytes: Vec<u8>,
+        }
+
+        #[derive(Debug)]
+        enum Message {
+            // A `seq` of 0 in the expected value matches any sequence number.
+            Data { seq: u32, payload: Box<Payload> },
+            Close,
+        }
+
+        impl Shape for Payload {
+            fn divergence(&self, expected: &Payload) -> Option<String> {
+                field_divergence("kind", self.kind, expected.kind).or_else(|| field_divergence("bytes", &self.bytes, &expected.bytes))
+            }
+        }
+
+        impl Shape for Message {
+            fn divergence(&self, expected: &Message) -> Option<String> {
+                match (self, expected) {
+                    (Message::Data { seq, payload }, Message::Data { seq: expected_seq, payload: expected_payload }) => {
+                        if *expected_seq != 0 && seq != expected_seq {
+                            return field_divergence("seq", seq, expected_seq);
+                        }
+                        field_divergence("payload", payload, expected_payload)
+                    }
+                    (Message::Close, Message::Close) => None,
+                    _ => Some(String::new()),
+                }
+            }
+        }
+
+        fn data(seq: u32, bytes: Vec<u8>) -> Message {
+            Message::Data { seq, payload: Box::new(Payload { kind: "raw", bytes }) }
+        }
+
+        #[test]
+        fn assert_matches_shape_works() {
+            assert_matches_shape!(data(7, vec![1, 2]), data(0, vec![1, 2]));
+            assert_matches_shape!(Message::Close, Message::Close);
+            assert_matches_shape!(vec![Some("a"), None], vec![Some("a"), None]);
+        }
+
+        #[test]
+        fn assert_matches_shape_reports_divergence() {
+            assert_eq!(data(7, vec![1, 2]).divergence(&data(0, vec![1])), Some(".payload.bytes.len".to_owned()));
+            assert_eq!(data(7, vec![1, 2]).divergence(&data(0, vec![1, 3])), Some(".payload.bytes[1]".to_owned()));
+            assert_eq!(data(7, vec![]).divergence(&data(8, vec![])), Some(".seq".to_owned()));
+            assert_eq!(Message::Close.divergence(&data(0, vec![])), Some(String::new()));
+        }
+
+        #[test]
+        #[should_panic(expected = "assertion failed: `[Some(1), None]` does not have the shape of `[Some(1), Some(2)]`: diverges at `[1]`")]
+        fn assert_matches_shape_panics() {
+            assert_matches_shape!(vec![Some(1), None], vec![Some(1), Some(2)]);
+        }
+
+        #[test]
+        #[should_panic(expected = "diverges at `.`")]
+        fn assert_matches_shape_panics_on_variant() {
+            assert_matches_shape!(Message::Close, data(1, vec![]));
+        }
+    }
+
+    #[test]
+    fn enumerate_matches_works() {
+        let items = vec![Ok(1), Err("a"), Ok(3), Ok(4)];
+        let pairs: Vec<_> = enumerate_matches!(&items, |i| Ok(n) => (i, *n)).collect();
+        assert_eq!(pairs, [(0, 1), (2, 3), (3, 4)]);
+        let odd: Vec<_> = enumerate_matches!(items, |i| Ok(n) if i % 2 == 1 => n * 10).collect();
+        assert_eq!(odd, [40]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn match_error_works() {
+        let error = crate::MatchError::new("Ok(_)", &Err::<u8, _>("eof"));
+        assert_eq!(error.pattern(), "Ok(_)");
+        assert_eq!(error.value(), "Err(\"eof\")");
+        assert_eq!(error.to_string(), "`Err(\"eof\")` does not match `Ok(_)`");
+    }
+
+    #[cfg(feature = "anyhow")]
+    mod try_match_anyhow {
+        use crate::MatchError;
+        use anyhow::Context;
+
+        fn frame_length(frame: &[u8]) -> anyhow::Result<u8> {
+            try_match_anyhow!(frame, [0xAA, length, ..] => *length).context("while parsing frame")
+        }
+
+        fn header(frame: &[u8]) -> anyhow::Result<(u8, u8)> {
+            let length = try_match_anyhow!(frame, [0xAA, length, ..] => *length)?;
+            let kind = try_match_anyhow!(frame.get(2), Some(kind @ 1..=3) => *kind)?;
+            Ok((length, kind))
+        }
+
+        #[test]
+        fn try_match_anyhow_works() {
+            assert_eq!(frame_length(&[0xAA, 3, 0, 0, 0]).unwrap(), 3);
+            let error = frame_length(&[0xBB]).unwrap_err();
+            assert_eq!(
+                error.chain().map(|cause| cause.to_string()).collect::<Vec<_>>(),
+                ["while parsing frame", "`[187]` does not match `[0xAA, length, ..]`"]
+            );
+        }
+
+        #[test]
+        fn try_match_anyhow_downcasts_to_match_error() {
+            assert_eq!(header(&[0xAA, 3, 2]).unwrap(), (3, 2));
+            let error = header(&[0xAA, 3, 7]).unwrap_err();
+            let mismatch = error.downcast_ref::<MatchError>().unwrap();
+            assert_eq!(mismatch.pattern(), "Some(kind @ 1..=3)");
+            assert_eq!(mismatch.value(), "Some(7)");
+            let error = frame_length(&[]).unwrap_err();
+            assert_eq!(error.root_cause().downcast_ref::<MatchError>().unwrap().value(), "[]");
+        }
+    }
+
+    #[allow(dead_code)]
+    #[derive(Debug)]
+    enum Column {
+        Int(i64),
+        Float(f64),
+        Missing,
+    }
+
+    #[test]
+    fn assert_all_same_match_works() {
+        assert_all_same_match!([Column::Int(1), Column::Int(-2)], Column::Int(_) | Column::Float(_));
+        assert_all_same_match!(&[Column::Float(0.5)][..], Column::Float(x) if x > 0.0);
+        assert_all_same_match!([] as [Column; 0], Column::Missing);
+    }
+
+    #[test]
+    #[should_panic(expected = "assertion failed: element 2 (`Float(1.5)`) is not the same variant as element 0 (`Int(1)`)")]
+    fn assert_all_same_match_panics_on_mixed_variants() {
+        assert_all_same_match!([Column::Int(1), Column::Int(2), Column::Float(1.5)], Column::Int(_) | Column::Float(_));
+    }
+
+    #[test]
+    #[should_panic(expected = "assertion failed: element 1 (`Missing`) does not match `Column::Int(_)`")]
+    fn assert_all_same_match_panics_on_mismatch() {
+        assert_all_same_match!([Column::Int(1), Column::Missing], Column::Int(_));
+    }
+
+    #[test]
+    fn array_all_match_works() {
+        let results: [Result<i32, ()>; 3] = [Ok(1), Ok(-2), Ok(3)];
+        assert!(array_all_match!(results, Ok(_)));
+        assert!(!array_all_match!(results, Ok(n) if n > 0));
+        assert!(!array_all_match!([Ok(1), Err(()), Ok(3)], Ok(_)));
+        assert!(array_all_match!([] as [Result<i32, ()>; 0], Err(_)));
+        assert!(array_all_match!([String::from("a"), String::from("b")], ref s if s.len() == 1));
+    }
+
+    #[test]
+    fn array_all_match_works_in_const() {
+        const PORTS: [Option<u16>; 3] = [Some(80), Some(443), None];
+        const ALL_SET: bool = array_all_match!(PORTS, Some(_));
+        const ALL_LOW: bool = array_all_match!(PORTS, Some(0..=1023) | None);
+        const _: () = assert!(!ALL_SET);
+        const _: () = assert!(ALL_LOW);
+    }
+
+    #[test]
+    fn unwrap_match_with_works() {
+        struct Recorder {
+            fallback: &'static str,
+            mismatches: Vec<String>,
+        }
+
+        impl<'a> crate::MatchSink<&'a str> for Recorder {
+            type Output = &'a str;
+
+            fn on_match(&mut self, result: &'a str) -> &'a str {
+                result
+            }
+
+            fn on_mismatch(&mut self, value: &dyn std::fmt::Debug, pattern: &'static str) -> &'a str {
+                self.mismatches.push(format!("{:?} !~ {}", value, pattern));
+                self.fallback
+            }
+        }
+
+        let mut recorder = Recorder { fallback: "anonymous", mismatches: Vec::new() };
+        assert_eq!(unwrap_match_with!(recorder, Some("alice"), Some(name) => name), "alice");
+        assert_eq!(unwrap_match_with!(recorder, None::<&str>, Some(name) => name), "anonymous");
+        assert_eq!(unwrap_match_with!(&mut recorder, Some(""), Some(name) if !name.is_empty() => name), "anonymous");
+        assert_eq!(recorder.mismatches, ["None !~ Some(name)", "Some(\"\") !~ Some(name) if !name.is_empty()"]);
+    }
+
+    #[test]
+    fn unwrap_match_with_builtin_sinks_work() {
+        assert_eq!(unwrap_match_with!(crate::PanicSink, Ok::<_, ()>(1), Ok(n) => n + 1), 2);
+        assert_eq!(unwrap_match_with!(crate::OptionSink, Ok::<_, ()>(1), Ok(n) => n + 1), Some(2));
+        assert_eq!(unwrap_match_with!(crate::OptionSink, Err::<u8, _>(()), Ok(n) => n), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "assertion failed: `Err(())` does not match `Ok(n)`")]
+    fn unwrap_match_with_panic_sink_panics() {
+        unwrap_match_with!(crate::PanicSink, Err::<u8, _>(()), Ok(n) => n);
+    }
+
+    #[cfg(feature = "serde_json")]
+    mod json_matches {
+        use serde_json::{json, Value};
+
+        fn message() -> Value {
+            json!({
+                "type": "ping",
+                "id": -7,
+                "tags": ["a", null, true],
+                "meta": { "retry": false, "delay": 2.5, "offset": u64::MAX },
+            })
+        }
+
+        #[test]
+        fn json_matches_works() {
+            assert!(json_matches!(message(), { "type": "ping", "id": _ }));
+            assert!(json_matches!(message(), { "id": (-7), "meta": { "retry": false } }));
+            assert!(json_matches!(message(), { "meta": { "delay": 2.5, "offset": (u64::MAX) } }));
+            assert!(json_matches!(message(), { "tags": ["a", null] }));
+            assert!(json_matches!(message(), { "tags": [_, _, true] }));
+            assert!(json_matches!(json!(3), 3));
+            assert!(json_matches!(json!(3), (3u64)));
+            assert!(json_matches!(json!(3), 3.0));
+            assert!(json_matches!(Value::Null, _));
+        }
+
+        #[test]
+        fn json_matches_rejects_mismatches() {
+            assert!(!json_matches!(message(), { "type": "pong" }));
+            assert!(!json_matches!(message(), { "type": "ping", "missing": _ }));
+            assert!(!json_matches!(message(), { "meta": { "retry": true } }));
+            assert!(!json_matches!(message(), { "meta": { "delay": 2 } }));
+            assert!(!json_matches!(message(), { "tags": [_, _, _, _] }));
+            assert!(!json_matches!(message(), ["a"]));
+            assert!(!json_matches!(json!("3"), 3));
+            assert!(!json_matches!(json!(false), null));
+        }
+    }
+
+    #[cfg(feature = "std")]
+    mod assert_matches_within {
+        use std::thread;
+        use std::time::Duration;
+
+        #[test]
+        fn assert_matches_within_works() {
+            assert_matches_within!(|| Some(2 + 2), Duration::from_secs(10), Some(n) if n == 4);
+        }
+
+        #[test]
+        #[should_panic(expected = "assertion failed: `|| thread::sleep(Duration::from_secs(5))` timed out after 50ms")]
+        fn assert_matches_within_panics_on_timeout() {
+            assert_matches_within!(|| thread::sleep(Duration::from_secs(5)), Duration::from_millis(50), ());
+        }
+
+        #[test]
+        #[should_panic(expected = "assertion failed: `Err(\"refused\")` does not match `Ok(_)`")]
+        fn assert_matches_within_panics_on_mismatch() {
+            assert_matches_within!(|| Err::<(), _>("refused"), Duration::from_secs(10), Ok(_));
+        }
+    }
+
+    #[test]
+    fn nth_match_works() {
+        let items = vec![Err("a"), Ok(1), Ok(2), Err("b"), Ok(3)];
+        assert_eq!(nth_match!(items.clone(), 0, Ok(n) => n), Some(1));
+        assert_eq!(nth_match!(items.clone(), 1, Ok(n) => n * 10), Some(20));
+        assert_eq!(nth_match!(items.clone(), 1, Err(e) => e), Some("b"));
+        assert_eq!(nth_match!(items, 3, Ok(n) => n), None);
+    }
+
+    #[test]
+    fn nth_match_short_circuits() {
+        let mut seen = 0;
+        let found = nth_match!((1..).inspect(|_| seen += 1), 1, n if n % 5 == 0 => n);
+        assert_eq!(found, Some(10));
+        assert_eq!(seen, 10);
+    }
+
+    assert_patterns_exhaustive!(Option<Result<u8, ()>>, [None, Some(Ok(0..=127)), Some(Ok(128..)), Some(Err(_))]);
+
+    #[test]
+    fn assert_patterns_exhaustive_works() {
+        #[allow(dead_code)]
+        enum Direction {
+            North,
+            South,
+            East(u8),
+        }
+
+        assert_patterns_exhaustive!(Direction, [Direction::North | Direction::South, Direction::East(n)]);
+        assert_patterns_exhaustive!((bool, bool), [(true, _), (false, true), (false, false)]);
+    }
+
+    #[test]
+    fn match_result_full_works() {
+        #[derive(Debug)]
+        enum Shape {
+            Rect { width: u32, height: u32 },
+            Circle(u32),
+        }
+
+        let result = match_result_full!(Shape::Rect { width: 2, height: 3 }, Shape::Rect { width, height } => { width, height });
+        assert!(result.matched);
+        assert_eq!(result.bindings, Some((2, 3)));
+
+        let result = match_result_full!(Shape::Circle(4), Shape::Rect { width, .. } => { width });
+        assert!(!result.matched);
+        assert_eq!(result.bindings, None);
+
+        let result = match_result_full!(Shape::Circle(4), Shape::Circle(r) if r > 1 => { r });
+        assert_eq!(result, crate::MatchResult { matched: true, bindings: Some((4,)) });
+    }
+
+    mod array_references {
+        // Arrays of a generic length cannot be matched with slice patterns (E0730), so they are sliced first.
+        fn first_positive<const N: usize>(array: &[i32; N]) -> Option<i32> {
+            option_match!(&array[..], [first, ..] if *first > 0 => *first)
+        }
+
+        #[test]
+        fn matches_works() {
+            let array = [3, -1, 4];
+            let array_ref = &array;
+            assert!(matches!(array_ref, [first, ..] if first > &0));
+            assert!(matches!(array_ref, [.., last] if *last == 4));
+            assert!(!matches!(array_ref, [_, second, _] if *second > 0));
+        }
+
+        #[test]
+        fn option_match_works() {
+            assert_eq!(first_positive(&[3, -1, 4]), Some(3));
+            assert_eq!(first_positive(&[-3]), None);
+            assert_eq!(first_positive(&[]), None);
+        }
+
+        #[test]
+        fn unwrap_match_works() {
+            let words = ["a".to_owned(), "bc".to_owned()];
+            let words_ref = &words;
+            let longest: &String = unwrap_match!(words_ref, [_, second] if second.len() > 1 => second);
+            assert_eq!(longest, "bc");
+        }
+
+        #[test]
+        fn assert_matches_works() {
+            let array_ref = &[Some(1), None];
+            assert_matches!(array_ref, [Some(n), None] if *n == 1);
+        }
+
+        #[test]
+        #[cfg(not(feature = "claim_compat"))]
+        #[should_panic(expected = "assertion failed: `[Some(1), None]` does not match `[Some(n), _] if *n > 1`")]
+        fn assert_matches_panics() {
+            let array_ref = &[Some(1), None];
+            assert_matches!(array_ref, [Some(n), _] if *n > 1);
+        }
+    }
+
+    #[derive(Debug)]
+    enum Frame {
+        Start,
+        Data(u8),
+        End,
+    }
+
+    #[test]
+    fn assert_sequence_matches_works() {
+        assert_sequence_matches!(vec![Frame::Start, Frame::Data(1), Frame::Data(2), Frame::End], [Frame::Start, repeat(Frame::Data(_)), Frame::End]);
+        assert_sequence_matches!(vec![Frame::Start, Frame::End], [Frame::Start, repeat(Frame::Data(_)), Frame::End]);
+        assert_sequence_matches!(&[Frame::Data(1), Frame::Data(9)], [repeat(&Frame::Data(n) if n < 5), &Frame::Data(_)]);
+        assert_sequence_matches!(Vec::<Frame>::new(), [repeat(_)]);
+        assert_sequence_matches!(vec![Frame::Start, Frame::End, Frame::End], [repeat(_), Frame::End]);
+    }
+
+    #[test]
+    #[should_panic(expected = "assertion failed: sequence ended after 2 elements without matching `[Frame")]
+    fn assert_sequence_matches_panics_on_missing_end() {
+        assert_sequence_matches!(vec![Frame::Start, Frame::Data(1)], [Frame::Start, repeat(Frame::Data(_)), Frame::End]);
+    }
+
+    #[test]
+    #[should_panic(expected = "assertion failed: element 2 (`Start`) does not continue the sequence `[Frame")]
+    fn assert_sequence_matches_panics_on_unexpected_element() {
+        assert_sequence_matches!(vec![Frame::Start, Frame::Data(1), Frame::Start, Frame::End], [Frame::Start, repeat(Frame::Data(_)), Frame::End]);
+    }
+
+    #[test]
+    fn option_match_reason_works() {
+        let mut reasons = Vec::new();
+        let values: Vec<u8> = [Some(1), None, Some(200), Some(3)]
+            .iter()
+            .filter_map(|item| option_match_reason!(*item, Some(n) if n < 100 => n).map_err(|reason| reasons.push(reason)).ok())
+            .collect();
+        assert_eq!(values, [1, 3]);
+        assert_eq!(reasons, ["Some(n) if n < 100", "Some(n) if n < 100"]);
+    }
+
+    #[test]
+    fn assert_preserves_match_works() {
+        let mut value: Result<u32, &str> = Ok(1);
+        assert_preserves_match!(&mut value, Ok(n) if n > 0, |v: &mut Result<u32, &str>| *v = v.map(|n| n * 2));
+        assert_eq!(value, Ok(2));
+    }
+
+    #[test]
+    #[should_panic(expected = "assertion failed: `Err(\"overflow\")` does not match `Ok(_)` after the mutation")]
+    fn assert_preserves_match_panics_after() {
+        let mut value: Result<u8, &str> = Ok(255);
+        assert_preserves_match!(&mut value, Ok(_), |v: &mut Result<u8, &str>| *v = v.and_then(|n| n.checked_add(1).ok_or("overflow")));
+    }
+
+    #[test]
+    #[should_panic(expected = "assertion failed: `Err(\"early\")` does not match `Ok(_)` before the mutation")]
+    fn assert_preserves_match_panics_before() {
+        let mut value: Result<u8, &str> = Err("early");
+        assert_preserves_match!(&mut value, Ok(_), |_: &mut Result<u8, &str>| ());
+    }
+
+    #[test]
+    fn variant_of_works() {
+        let reference = Token::Number(0);
+        let token = Token::Number(7);
+        assert!(matches!(token, Token::Number(n) if n > 0 && variant_of!(token) == variant_of!(reference)));
+        assert!(!matches!(Token::Eof, ref t if variant_of!(*t) == variant_of!(reference)));
+        assert_matches!(Token::Ident { name: "x" }, ref t if variant_of!(*t) == variant_of!(Token::Ident { name: "y" }));
+    }
+
+    #[test]
+    #[cfg(not(feature = "claim_compat"))]
+    #[should_panic(expected = "assertion failed: `Number(1)` does not match `ref t if variant_of!(")]
+    fn variant_of_panics_in_assert_matches() {
+        let reference = Token::Eof;
+        assert_matches!(Token::Number(1), ref t if variant_of!(*t) == variant_of!(reference));
+    }
+
+    #[test]
+    fn extract_opt_works() {
+        fn first_even(items: &[u32]) -> Option<u32> {
+            let first = extract_opt!(items, [first, ..] if first % 2 == 0 => *first);
+            Some(first * 10)
+        }
+
+        assert_eq!(first_even(&[2, 3]), Some(20));
+        assert_eq!(first_even(&[3, 2]), None);
+        assert_eq!(first_even(&[]), None);
+    }
+
+    #[test]
+    fn extract_res_works() {
+        #[derive(Debug, PartialEq)]
+        struct ParseError(&'static str);
+
+        impl From<&'static str> for ParseError {
+            fn from(message: &'static str) -> ParseError {
+                ParseError(message)
+            }
+        }
+
+        fn header(bytes: &[u8]) -> Result<u8, ParseError> {
+            let version = extract_res!(bytes, [b'V', version, ..] => *version, "missing header");
+            Ok(version)
+        }
+
+        assert_eq!(header(b"V\x02rest"), Ok(2));
+        assert_eq!(header(b"X"), Err(ParseError("missing header")));
+    }
+
+    #[test]
+    fn assert_matches_eq_works() {
+        assert_matches_eq!(Some("abc".to_owned()), Some(s) => s, "abc");
+        assert_matches_eq!(Ok::<_, ()>((1, 2)), Ok((a, _)) if a > 0 => a, 1);
+    }
+
+    #[test]
+    fn assert_matches_eq_panics_on_unequal() {
+        assert_eq!(
+            panic_message(|| assert_matches_eq!(Ok::<_, ()>((1, 2)), Ok((_, b)) => b, 3)),
+            "assertion failed: `Ok((_, b))` matched, but `b == 3` failed\n  left: 2\n right: 3"
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "claim_compat"))]
+    #[should_panic(expected = "assertion failed: `Err(())` does not match `Ok((_, b))`")]
+    fn assert_matches_eq_panics_on_mismatch() {
+        assert_matches_eq!(Err::<(u8, u8), _>(()), Ok((_, b)) => b, 3);
+    }
+
+    #[cfg_attr(not(debug_assertions), allow(unused_variables))]
+    fn sample_check(value: Option<u8>, evaluated: &mut u32) {
+        sample_assert_matches!({ *evaluated += 1; value }, Some(_), 3);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn sample_assert_matches_skips_unsampled_calls() {
+        let mut evaluated = 0;
+        for value in [Some(1), None, None, Some(4), Some(5), None] {
+            sample_check(value, &mut evaluated);
+        }
+        assert_eq!(evaluated, 2);
+    }
+
+    #[test]
+    #[cfg(all(debug_assertions, not(feature = "claim_compat")))]
+    #[should_panic(expected = "does not match `Some(n) if n < 3`")]
+    fn sample_assert_matches_panics_on_sampled_call() {
+        for n in 0..10 {
+            sample_assert_matches!(Some(n), Some(n) if n < 3, 3);
+        }
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "sample_assert_matches! needs `every` to be at least 1, got 0")]
+    fn sample_assert_matches_rejects_zero() {
+        sample_assert_matches!(Some(1), Some(_), 0);
+    }
+
+    #[test]
+    #[cfg(not(debug_assertions))]
+    fn sample_assert_matches_is_a_no_op_without_debug_assertions() {
+        let mut evaluated = 0;
+        for _ in 0..6 {
+            sample_check(None, &mut evaluated);
+        }
+        assert_eq!(evaluated, 0);
+    }
+
+    #[test]
+    fn assert_covers_variants_works() {
+        assert_covers_variants!([Token::Number(1), Token::Ident { name: "x" }, Token::Eof], [Token::Eof, Token::Number, Token::Ident]);
+        assert_covers_variants!(&[Token::Number(1), Token::Eof], [Token::Number, Token::Eof]);
+    }
+
+    #[test]
+    #[should_panic(expected = "assertion failed: variants never seen: `Token::Number`, `Token::Ident`")]
+    fn assert_covers_variants_panics() {
+        assert_covers_variants!([Token::Eof, Token::Eof], [Token::Eof, Token::Number, Token::Ident]);
+    }
+
+    #[test]
+    fn unwrap_ok_matches_works() {
+        let result: Result<Option<u8>, &str> = Ok(Some(4));
+        assert_eq!(unwrap_ok_matches!(result, Some(n) if n % 2 == 0 => n / 2), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "assertion failed: expected `Ok` matching `Some(_)`, got `Err(\"disconnected\")`")]
+    fn unwrap_ok_matches_panics_on_err() {
+        unwrap_ok_matches!(Err::<Option<u8>, _>("disconnected"), Some(_) => ());
+    }
+
+    #[test]
+    #[should_panic(expected = "assertion failed: `Ok` value `None` does not match `Some(_)`")]
+    fn unwrap_ok_matches_panics_on_inner_mismatch() {
+        unwrap_ok_matches!(Ok::<_, ()>(None::<u8>), Some(_) => ());
+    }
+
+    #[test]
+    fn match_or_log_works() {
+        #[derive(Debug, PartialEq)]
+        enum Packet {
+            Ping,
+            Data(Vec<u8>),
+            Unknown(u8),
+        }
+
+        let mut logged = Vec::new();
+        let mut handle = |packet| {
+            match_or_log!(packet, {
+                Packet::Ping => 0,
+                Packet::Data(ref bytes) if !bytes.is_empty() => bytes.len(),
+            }, |unknown| {
+                logged.push(format!("unhandled {:?}", unknown));
+                usize::MAX
+            })
+        };
+
+        assert_eq!(handle(Packet::Ping), 0);
+        assert_eq!(handle(Packet::Data(vec![1, 2])), 2);
+        assert_eq!(handle(Packet::Data(Vec::new())), usize::MAX);
+        assert_eq!(handle(Packet::Unknown(7)), usize::MAX);
+        assert_eq!(logged, ["unhandled Data([])", "unhandled Unknown(7)"]);
+    }
+
+    #[test]
+    fn bytes_match_works() {
+        let request: &[u8] = b"GET /index.html\r\n";
+        assert!(bytes_match!(request, b"GET"));
+        assert!(bytes_match!(request, "GET /"));
+        #[cfg(feature = "std")]
+        assert!(bytes_match!(request.to_vec(), b""));
+        assert!(bytes_match!(&request[15..], b"\r\n"));
+        assert!(!bytes_match!(request, b"PUT"));
+        assert!(!bytes_match!(b"GE", b"GET"));
+    }
+
+    #[test]
+    fn bytes_eq_match_works() {
+        assert!(bytes_eq_match!(b"GET", b"GET"));
+        #[cfg(feature = "std")]
+        assert!(bytes_eq_match!(String::from("\tx"), b"\x09x"));
+        assert!(!bytes_eq_match!(b"GET /", b"GET"));
+        assert!(!bytes_eq_match!(b"POST", "GET"));
+    }
+
+    #[test]
+    fn unwrap_match_send_works() {
+        #[derive(Debug)]
+        enum Job {
+            Run(std::sync::Arc<Vec<u8>>),
+            Stop,
+        }
+        let job = Job::Run(std::sync::Arc::new(vec![1, 2]));
+        let data = unwrap_match_send!(job, Job::Run(data) if !data.is_empty() => data);
+        assert_eq!(std::thread::spawn(move || data.len()).join().unwrap(), 2);
+        assert_eq!(unwrap_match_send!(Job::Stop, Job::Stop => 3, "not stopped"), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "assertion failed: `None` does not match `Some(x)`")]
+    fn unwrap_match_send_panics() {
+        let _: u8 = unwrap_match_send!(None, Some(x) => x);
+    }
+
+    #[cfg(feature = "std")]
+    mod collect_matches_recursive {
+        enum Tree {
+            Leaf(u32),
+            Node(&'static str, Vec<Tree>),
+        }
+
+        impl Tree {
+            fn children(&self) -> impl Iterator<Item = &Tree> {
+                match self {
+                    Tree::Leaf(_) => [].iter(),
+                    Tree::Node(_, children) => children.iter(),
+                }
+            }
+        }
+
+        fn tree() -> Tree {
+            Tree::Node("root", vec![
+                Tree::Leaf(1),
+                Tree::Node("left", vec![Tree::Leaf(2), Tree::Node("empty", vec![])]),
+                Tree::Leaf(3),
+                Tree::Node("right", vec![Tree::Leaf(4)]),
+            ])
+        }
+
+        #[test]
+        fn collects_leaves_in_order() {
+            let tree = tree();
+            let leaves = collect_matches_recursive!(&tree, |node| node.children(), Tree::Leaf(value) => *value);
+            assert_eq!(leaves, [1, 2, 3, 4]);
+        }
+
+        #[test]
+        fn guard_and_root() {
+            let tree = tree();
+            let names = collect_matches_recursive!(&tree, |node| node.children(), Tree::Node(name, children) if !children.is_empty() => *name);
+            assert_eq!(names, ["root", "left", "right"]);
+            let odd = collect_matches_recursive!(&tree, |node| node.children(), Tree::Leaf(value @ (1 | 3)) => *value);
+            assert_eq!(odd, [1, 3]);
+        }
+
+        #[test]
+        fn empty_when_nothing_matches() {
+            let tree = tree();
+            let found = collect_matches_recursive!(&tree, |node| node.children(), Tree::Leaf(value) if *value > 10 => *value);
+            assert!(found.is_empty());
+            let leaf = Tree::Leaf(5);
+            let found = collect_matches_recursive!(&leaf, |node| node.children(), Tree::Node(name, _) => *name);
+            assert!(found.is_empty());
+        }
+    }
+
+    #[test]
+    fn assert_matches_complement_works() {
+        let value: Result<i32, &str> = Ok(50);
+        assert_matches_complement!(value, [Err(_), Ok(i32::MIN..=0), Ok(n) if n > 100]);
+        assert_matches_complement!(Some(3), [None, Some(0)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "assertion failed: `Ok(150)` unexpectedly matched `Ok(n) if n > 100`")]
+    fn assert_matches_complement_panics() {
+        let value: Result<i32, &str> = Ok(150);
+        assert_matches_complement!(value, [Err(_), Ok(i32::MIN..=0), Ok(n) if n > 100]);
+    }
+
+    #[test]
+    #[should_panic(expected = "`Err(\"x\")` unexpectedly matched `Err(_)`")]
+    fn assert_matches_complement_names_first_match() {
+        let value: Result<i32, &str> = Err("x");
+        assert_matches_complement!(value, [Ok(_), Err(_), Err("x")]);
+    }
+
+    mod unwrap_match_mut {
+        #[derive(Debug, PartialEq)]
+        enum Slot {
+            Free,
+            Used { name: String, data: Vec<u8> },
+        }
+
+        fn data(slots: &mut [Slot], index: usize) -> &mut Vec<u8> {
+            unwrap_match_mut!(&mut slots[index], Slot::Used { ref mut data, .. } => data)
+        }
+
+        #[test]
+        fn mutates_in_place() {
+            let mut slots = vec![Slot::Free, Slot::Used { name: "a".into(), data: vec![1] }];
+            data(&mut slots, 1).push(2);
+            let name: &mut String = unwrap_match_mut!(&mut slots[1], Slot::Used { name, .. } => name);
+            name.push('b');
+            assert_eq!(slots[1], Slot::Used { name: "ab".into(), data: vec![1, 2] });
+        }
+
+        #[test]
+        fn guard_and_message() {
+            let mut slot = Slot::Used { name: "a".into(), data: vec![] };
+            let data = unwrap_match_mut!(&mut slot, Slot::Used { data, .. } if data.is_empty() => data, "slot {} is not empty", 0);
+            data.extend_from_slice(b"xy");
+            assert_eq!(slot, Slot::Used { name: "a".into(), data: b"xy".to_vec() });
+        }
+
+        #[test]
+        #[should_panic(expected = "assertion failed: `Free` does not match `Slot::Used { ref mut data, .. }`")]
+        fn panics() {
+            let mut slots = [Slot::Free];
+            data(&mut slots, 0);
+        }
+    }
+
+    #[test]
+    fn option_match_try_works() {
+        fn even_length(entry: Result<&str, u8>) -> Option<usize> {
+            option_match_try!(entry, Ok(text), text.parse::<u32>().ok()? % 2 == 0 => text.len())
+        }
+        assert_eq!(even_length(Ok("1234")), Some(4));
+        assert_eq!(even_length(Ok("123")), None);
+        assert_eq!(even_length(Ok("abc")), None);
+        assert_eq!(even_length(Err(0)), None);
+
+        let pairs = [Some((1i32, 2i32)), Some((3, 0)), None];
+        let quotients: Vec<i32> = pairs
+            .iter()
+            .filter_map(|pair| option_match_try!(pair, Some((a, b)), a.checked_div(*b)? < 2 => a / b))
+            .collect();
+        assert_eq!(quotients, [0]);
+    }
+
+    mod slice_match_mut {
+        fn sort_pair(pair: &mut [i32]) -> bool {
+            match slice_match_mut!(&mut *pair, [a, b] if *a > *b => (a, b)) {
+                Some((a, b)) => {
+                    core::mem::swap(a, b);
+                    true
+                }
+                None => false,
+            }
+        }
+
+        #[test]
+        fn swaps_when_matched() {
+            let mut pair = [2, 1];
+            assert!(sort_pair(&mut pair));
+            assert_eq!(pair, [1, 2]);
+            assert!(!sort_pair(&mut pair));
+            assert_eq!(pair, [1, 2]);
+        }
+
+        #[test]
+        fn length_must_match() {
+            let mut values = vec![5, 4, 3];
+            assert!(!sort_pair(&mut values));
+            assert_eq!(values, [5, 4, 3]);
+            assert_eq!(slice_match_mut!(&mut values, [_, _] => ()), None);
+        }
+
+        #[test]
+        fn edits_specific_positions() {
+            let mut fields = [Some(1), None, Some(3)];
+            if let Some(middle) = slice_match_mut!(&mut fields, [Some(_), middle @ None, Some(_)] => middle) {
+                *middle = Some(2);
+            }
+            assert_eq!(fields, [Some(1), Some(2), Some(3)]);
+            let first = slice_match_mut!(&mut fields, [Some(first), Some(2), _] => first);
+            *first.unwrap() += 10;
+            assert_eq!(fields, [Some(11), Some(2), Some(3)]);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    mod try_match_err {
+        use std::error::Error;
+        use std::num::ParseIntError;
+
+        #[derive(Debug, PartialEq, thiserror::Error)]
+        enum ProtocolError {
+            #[error("expected a frame matching `{pattern}`")]
+            UnexpectedFrame { pattern: &'static str },
+            #[error("mismatch: `{0}`")]
+            Mismatch(&'static str),
+            #[error("invalid length")]
+            Length(#[from] ParseIntError),
+        }
+
+        enum Frame {
+            Ping(u8),
+            Data(Vec<u8>),
+        }
+
+        fn data(frame: Frame) -> Result<Vec<u8>, Box<dyn Error>> {
+            let bytes = try_match_err!(frame, Frame::Data(bytes) if !bytes.is_empty() => bytes, |pattern| ProtocolError::UnexpectedFrame { pattern })?;
+            Ok(bytes)
+        }
+
+        fn ping(length: &str, frame: Frame) -> Result<u8, ProtocolError> {
+            let length: u8 = length.parse()?;
+            try_match_err!(frame, Frame::Ping(n) if n == length => n, ProtocolError::Mismatch)
+        }
+
+        #[test]
+        fn ok_path() {
+            assert_eq!(data(Frame::Data(vec![1])).unwrap(), [1]);
+            assert_eq!(try_match_err!(Frame::Ping(3), Frame::Ping(n) => n, ProtocolError::Mismatch), Ok(3));
+            assert_eq!(ping("3", Frame::Ping(3)), Ok(3));
+        }
+
+        #[test]
+        fn err_path() {
+            assert_eq!(data(Frame::Ping(0)).unwrap_err().to_string(), "expected a frame matching `Frame::Data(bytes) if !bytes.is_empty()`");
+            assert_eq!(
+                try_match_err!(Frame::Data(vec![]), Frame::Ping(_) => (), ProtocolError::Mismatch),
+                Err(ProtocolError::Mismatch("Frame::Ping(_)"))
+            );
+            assert_eq!(ping("3", Frame::Ping(4)), Err(ProtocolError::Mismatch("Frame::Ping(n) if n == length")));
+            assert_eq!(ping("3", Frame::Ping(4)).unwrap_err().to_string(), "mismatch: `Frame::Ping(n) if n == length`");
+        }
+
+        #[test]
+        fn source_errors_convert_alongside_mismatches() {
+            let error = ping("x", Frame::Ping(3)).unwrap_err();
+            assert_matches!(error, ProtocolError::Length(_));
+            assert_eq!(error.source().unwrap().to_string(), "invalid digit found in string");
+            assert!(ping("3", Frame::Data(vec![3])).unwrap_err().source().is_none());
+        }
+    }
+
+    mod method_chain_temporaries {
+        #[derive(Debug, PartialEq)]
+        enum Mode {
+            Tcp { port: u16 },
+            Unix(String),
+        }
+
+        #[derive(Debug)]
+        struct Section {
+            mode: Mode,
+        }
+
+        impl Section {
+            fn mode(&self) -> &Mode {
+                &self.mode
+            }
+        }
+
+        struct Config;
+
+        impl Config {
+            // Returns an owned value, so `.unwrap().mode()` borrows from a temporary.
+            fn section(&self, name: &str) -> Option<Section> {
+                match name {
+                    "net" => Some(Section { mode: Mode::Tcp { port: 80 } }),
+                    "ipc" => Some(Section { mode: Mode::Unix("/run/app.sock".into()) }),
+                    _ => None,
+                }
+            }
+        }
+
+        #[test]
+        fn checks_and_assertions() {
+            let config = Config;
+            assert!(matches!(config.section("net").unwrap().mode(), Mode::Tcp { port: 80 }));
+            assert!(!matches!(config.section("ipc").unwrap().mode(), Mode::Unix(path) if path.is_empty()));
+            assert_matches!(config.section("net").unwrap().mode(), Mode::Tcp { port } if *port < 1024);
+            assert_matches!(config.section("ipc").unwrap().mode(), Mode::Unix(_), "no socket for {}", "ipc");
+            debug_assert_matches!(config.section("net").unwrap().mode(), Mode::Tcp { .. });
+        }
+
+        #[test]
+        fn extracting_copies_and_owned_values() {
+            let config = Config;
+            let port = unwrap_match!(config.section("net").unwrap().mode(), Mode::Tcp { port } => *port);
+            assert_eq!(port, 80);
+            let path = option_match!(config.section("ipc").unwrap().mode(), Mode::Unix(path) => path.clone());
+            assert_eq!(path.as_deref(), Some("/run/app.sock"));
+            let length = try_unwrap_match!(config.section("ipc").unwrap().mode(), Mode::Unix(path) => path.len());
+            assert_eq!(length, Ok(13));
+        }
+
+        #[test]
+        #[should_panic(expected = "Unix(\"/run/app.sock\")")]
+        fn mismatch_reports_the_borrowed_value() {
+            let config = Config;
+            assert_matches!(config.section("ipc").unwrap().mode(), Mode::Tcp { .. });
+        }
+    }
+
+    mod assert_prefix_then_none {
+        #[derive(Clone, Debug)]
+        enum Packet {
+            Syn(u32),
+            Ack,
+            Data(&'static str),
+        }
+
+        fn stream() -> Vec<Packet> {
+            vec![Packet::Syn(1), Packet::Syn(2), Packet::Ack, Packet::Data("a"), Packet::Data("b")]
+        }
+
+        #[test]
+        fn conforming_stream() {
+            assert_prefix_then_none!(stream(), 2, Packet::Syn(_), Packet::Syn(_));
+            assert_prefix_then_none!(stream().iter(), 3, Packet::Syn(_) | Packet::Ack, Packet::Data("") | Packet::Ack);
+            assert_prefix_then_none!(stream(), 2, Packet::Syn(n) if n < 10, Packet::Data(s) if s.is_empty());
+            assert_prefix_then_none!(stream(), 0, Packet::Ack, Packet::Data(s) if s.len() > 1);
+            assert_prefix_then_none!(Vec::<Packet>::new(), 0, _, _);
+        }
+
+        #[test]
+        #[should_panic(expected = "assertion failed: element 1 (`Ack`) of the prefix does not match `Packet::Syn(_)`")]
+        fn prefix_violation() {
+            let packets = [Packet::Syn(1), Packet::Ack, Packet::Syn(2)];
+            assert_prefix_then_none!(packets.iter(), 3, Packet::Syn(_), Packet::Syn(_));
+        }
+
+        #[test]
+        #[should_panic(expected = "assertion failed: element 4 (`Syn(7)`) after the prefix unexpectedly matched `Packet::Syn(_)`")]
+        fn forbidden_element_later() {
+            let mut packets = stream();
+            packets.insert(4, Packet::Syn(7));
+            assert_prefix_then_none!(packets, 2, Packet::Syn(_), Packet::Syn(_));
+        }
+
+        #[test]
+        #[should_panic(expected = "assertion failed: expected a prefix of 4 elements matching `Packet::Syn(_)`, but there were only 2")]
+        fn stream_ends_early() {
+            assert_prefix_then_none!(stream()[..2].to_vec(), 4, Packet::Syn(_), Packet::Ack);
+        }
+    }
+
+    #[cfg(feature = "defmt")]
+    mod defmt_warn_matches {
+        use std::cell::Cell;
+
+        thread_local! {
+            static LOGGED: Cell<usize> = const { Cell::new(0) };
+        }
+
+        // Counts the messages logged on each thread, which is all the tests need from the encoded frames.
+        #[defmt::global_logger]
+        struct Logger;
+
+        unsafe impl defmt::Logger for Logger {
+            fn acquire() {
+                LOGGED.with(|logged| logged.set(logged.get() + 1));
+            }
+
+            unsafe fn flush() {}
+
+            unsafe fn release() {}
+
+            unsafe fn write(_bytes: &[u8]) {}
+        }
+
+        defmt::timestamp!("");
+
+        #[test]
+        fn warns_only_on_mismatch() {
+            assert!(defmt_warn_matches!(Some(3u8), Some(1..=9)));
+            assert!(!defmt_warn_matches!(Some(30u8), Some(n) if n < 10));
+            assert!(defmt_warn_matches!(0x81u8, 0x80..=0x8F));
+            assert_eq!(LOGGED.with(Cell::get), 1);
+        }
+    }
+
+    mod assert_roundtrip_matches {
+        #[derive(Debug, PartialEq)]
+        enum Message {
+            Ping(u8),
+            Text(String),
+        }
+
+        fn encode(message: Message) -> Vec<u8> {
+            match message {
+                Message::Ping(n) => vec![0, n],
+                Message::Text(text) => [&[1][..], text.as_bytes()].concat(),
+            }
+        }
+
+        fn decode(bytes: &[u8]) -> Option<Message> {
+            match bytes {
+                [0, n] => Some(Message::Ping(*n)),
+                [1, text @ ..] => String::from_utf8(text.to_vec()).ok().map(Message::Text),
+                _ => None,
+            }
+        }
+
+        // Decodes every message as a ping.
+        fn decode_wrong(bytes: &[u8]) -> Option<Message> {
+            bytes.last().map(|n| Message::Ping(*n))
+        }
+
+        #[test]
+        fn roundtrip_works() {
+            assert_roundtrip_matches!(Message::Ping(3), encode, decode, Some(Message::Ping(3)));
+            assert_roundtrip_matches!(Message::Text("hi".into()), encode, decode, Some(Message::Text(ref s)) if s == "hi");
+            assert_roundtrip_matches!(7u32, |n: u32| n.to_string(), |s: &String| s.parse::<u32>(), Ok(7));
+        }
+
+        #[test]
+        #[should_panic(expected = "assertion failed: round trip of `Message::Text(\"hi\".into())` does not match `Some(Message::Text(_))`\n decoded: Some(Ping(105))\n encoded: [1, 104, 105]")]
+        fn wrong_variant_panics() {
+            assert_roundtrip_matches!(Message::Text("hi".into()), encode, decode_wrong, Some(Message::Text(_)));
+        }
+    }
+
+    mod impl_try_into_variants {
+        use core::convert::{TryFrom, TryInto};
+
+        #[derive(Debug, PartialEq)]
+        enum Token {
+            Number(u32),
+            Word(String),
+            End,
+        }
+
+        #[derive(Debug, PartialEq)]
+        enum Only {
+            One(u8),
+        }
+
+        impl_try_into_variants!(Token, { Number => u32, Word => String });
+        impl_try_into_variants!(Only, { One => u8 });
+
+        #[test]
+        fn extracts_the_variant() {
+            assert_eq!(u32::try_from(Token::Number(7)), Ok(7));
+            let word: Result<String, Token> = Token::Word("hi".into()).try_into();
+            assert_eq!(word, Ok("hi".to_string()));
+            assert_eq!(u8::try_from(Only::One(1)), Ok(1));
+        }
+
+        #[test]
+        fn returns_the_original_on_mismatch() {
+            assert_eq!(u32::try_from(Token::Word("hi".into())), Err(Token::Word("hi".into())));
+            let end: Result<String, Token> = Token::End.try_into();
+            assert_eq!(end, Err(Token::End));
+        }
+    }
+
+    #[cfg(feature = "std")]
+    mod assert_matches_unordered {
+        #[derive(Debug)]
+        enum Event {
+            Open(u8),
+            Close,
+        }
+
+        #[test]
+        fn any_order_passes() {
+            let events = [Event::Close, Event::Open(2), Event::Open(1)];
+            assert_matches_unordered!(events.iter(), [Event::Open(1), Event::Open(2), Event::Close]);
+            // A greedy pairing would give `Open(1)` to `Event::Open(_)` and leave nothing for `Event::Open(1)`.
+            let events = [Event::Open(1), Event::Open(5)];
+            assert_matches_unordered!(events.iter(), [Event::Open(_), Event::Open(n) if *n < 3]);
+            assert_matches_unordered!(vec![Some(2)], [Some(_) | None]);
+        }
+
+        #[test]
+        #[should_panic(expected = "assertion failed: `events` does not match `[Event::Open(_), Event::Open(_), Event::Close]` in any order; missing: `Event::Open(_)`")]
+        fn missing_pattern_panics() {
+            let events = vec![Event::Close, Event::Open(2)];
+            assert_matches_unordered!(events, [Event::Open(_), Event::Open(_), Event::Close]);
+        }
+
+        #[test]
+        #[should_panic(expected = "in any order; unexpected: `Open(3)`")]
+        fn extra_element_panics() {
+            let events = vec![Event::Open(1), Event::Close, Event::Open(3)];
+            assert_matches_unordered!(events, [Event::Open(_), Event::Close]);
+        }
+
+        #[test]
+        #[should_panic(expected = "in any order; missing: `Event::Open(2)`; unexpected: `Open(4)`")]
+        fn wrong_element_panics() {
+            let events = vec![Event::Open(4), Event::Close];
+            assert_matches_unordered!(events, [Event::Close, Event::Open(2)]);
+        }
+    }
+
+    #[cfg(feature = "backtrace")]
+    mod assert_matches_bt {
+        use std::backtrace::{Backtrace, BacktraceStatus};
+
+        #[test]
+        fn assert_matches_bt_works() {
+            assert_matches_bt!(Some(3), Some(1..=5));
+            assert_matches_bt!(Ok::<u8, ()>(1), Ok(n) if n > 0);
+        }
+
+        #[inline(never)]
+        fn check_port(port: Option<u16>) {
+            assert_matches_bt!(port, Some(1..=1023));
+        }
+
+        #[test]
+        fn message_contains_backtrace_if_enabled() {
+            let message = super::panic_message(|| check_port(Some(8080)));
+            assert!(message.starts_with("assertion failed: "));
+            assert!(message.contains("Some(8080)"));
+            if Backtrace::capture().status() == BacktraceStatus::Captured {
+                assert!(message.contains("\nmismatch backtrace:\n"));
+                assert!(message.contains("check_port"));
+            } else {
+                assert!(!message.contains("backtrace"));
+            }
+        }
+    }
+
+    mod multi_match {
+        use core::cell::Cell;
+
+        #[derive(Debug)]
+        enum Field {
+            Named(&'static str, i32),
+            Number(i32),
+        }
+
+        fn facets(field: &Field) -> (Option<&str>, Option<i32>) {
+            multi_match!(*field, {
+                Field::Named(name, _) => *name,
+                Field::Named(_, n) | Field::Number(n) if *n >= 0 => *n,
+            })
+        }
+
+        #[test]
+        fn both_one_or_neither() {
+            assert_eq!(facets(&Field::Named("x", 1)), (Some("x"), Some(1)));
+            assert_eq!(facets(&Field::Named("x", -1)), (Some("x"), None));
+            assert_eq!(facets(&Field::Number(2)), (None, Some(2)));
+            assert_eq!(facets(&Field::Number(-2)), (None, None));
+            let (only,) = multi_match!(Some(String::from("a")), { Some(s) => s.len() });
+            assert_eq!(only, Some(1));
+        }
+
+        #[test]
+        fn evaluates_once() {
+            let calls = Cell::new(0);
+            let next = || {
+                calls.set(calls.get() + 1);
+                Field::Named("y", calls.get())
+            };
+            let (name, number, other) = multi_match!(next(), {
+                Field::Named(name, _) => *name,
+                Field::Named(_, n) => *n,
+                Field::Number(_) => (),
+            });
+            assert_eq!((name, number, other), (Some("y"), Some(1), None));
+            assert_eq!(calls.get(), 1);
+        }
+    }
+
+    mod assert_matches_table {
+        fn classify(n: i32) -> Option<&'static str> {
+            match n {
+                0 => None,
+                n if n > 0 => Some("positive"),
+                _ => Some("negative"),
+            }
+        }
+
+        #[test]
+        fn assert_matches_table_works() {
+            assert_matches_table!(classify, [
+                (1, Some("positive")),
+                (-4, Some(s) if s.starts_with("neg")),
+                (0, None),
+            ]);
+            assert_matches_table!(|v: Vec<u8>| v.len(), [(vec![], 0), (vec![1, 2], 1..=2)]);
+        }
+
+        #[test]
+        #[should_panic(expected = "assertion failed: row 1 (`2 - 7`): `Some(\"negative\")` does not match `Some(\"positive\")`")]
+        fn assert_matches_table_panics() {
+            assert_matches_table!(classify, [
+                (3, Some("positive")),
+                (2 - 7, Some("positive")),
+                (0, None),
+            ]);
+        }
+    }
+
+    #[test]
+    fn assert_ok_works() {
+        let parsed: Result<Vec<u8>, String> = Ok(vec![1, 2]);
+        let mut bytes = assert_ok!(parsed);
+        bytes.push(3);
+        assert_eq!(bytes, [1, 2, 3]);
+        assert_eq!(assert_ok!(Ok::<_, ()>(5), "no value for {}", "x") + 1, 6);
+    }
+
+    #[test]
+    fn assert_err_works() {
+        let error = assert_err!("-1".parse::<u8>());
+        assert_eq!(error.to_string(), "invalid digit found in string");
+        assert_eq!(assert_err!(Err::<(), _>("bad"), "expected an error"), "bad");
+    }
+
+    #[test]
+    fn assert_ok_and_err_panic() {
+        let message = panic_message(|| {
+            assert_ok!(Err::<u8, _>("disk full"));
+        });
+        assert_eq!(message, "assertion failed: expected `Ok`, got `Err(\"disk full\")`");
+        let message = panic_message(|| {
+            assert_err!(Ok::<_, ()>(vec![1]));
+        });
+        assert_eq!(message, "assertion failed: expected `Err`, got `Ok([1])`");
+        let message = panic_message(|| {
+            assert_ok!(Err::<u8, _>(()), "step {} failed", 2);
+        });
+        assert_eq!(message, "step 2 failed");
+    }
+
+    #[test]
+    fn matches_with_works() {
+        let mut evens = Vec::new();
+        let values = [Some(1), Some(2), None, Some(4)];
+        let matched: Vec<bool> = values
+            .iter()
+            .map(|value| matches_with!(&mut evens, value, Some(n) if { evens.push(*n); n % 2 == 0 }))
+            .collect();
+        assert_eq!(matched, [false, true, false, true]);
+        // The guard runs only when `Some(n)` matched, so `None` pushed nothing.
+        assert_eq!(evens, [1, 2, 4]);
+
+        let mut evaluations = 0;
+        let mut guards = 0;
+        let mut next = || {
+            evaluations += 1;
+            Err::<u8, u8>(evaluations)
+        };
+        assert!(!matches_with!(&mut guards, next(), Ok(_) if { *guards += 1; true }));
+        assert!(matches_with!(&mut guards, next(), Err(2)));
+        assert!(matches_with!(&mut guards, Ok::<u8, u8>(0), Ok(_) if { *guards += 1; true }));
+        assert_eq!((evaluations, guards), (2, 1));
+    }
+
+    #[test]
+    fn reject_match_works() {
+        let name = reject_match!(String::from("alice"), [ref s if s.is_empty(), ref s if s.contains(' ')]);
+        assert_eq!(name, "alice");
+        let port = reject_match!("8080".parse::<u16>().unwrap(), [0, 1..=1023]).to_string();
+        assert_eq!(port, "8080");
+    }
+
+    #[test]
+    #[should_panic(expected = "assertion failed: `Some(\"\")` unexpectedly matched `Some(\"\")`")]
+    fn reject_match_panics() {
+        let _ = reject_match!(Some(""), [None, Some(""), Some(_)]);
+    }
+
+    mod pattern_check {
+        use crate::PatternCheck;
+
+        enum Frame {
+            Data(Vec<u8>),
+            Close,
+        }
+
+        define_pattern_check!(Frame, Frame::Data(ref bytes) if !bytes.is_empty());
+        define_pattern_check!(Option<u8>, Some(1..=9) | None);
+
+        fn all_valid<T: PatternCheck>(values: &[T]) -> bool {
+            values.iter().all(|value| check_matches!(value))
+        }
+
+        #[test]
+        fn pattern_check_works() {
+            assert!(all_valid(&[Frame::Data(vec![1]), Frame::Data(vec![2, 3])]));
+            assert!(!all_valid(&[Frame::Data(vec![1]), Frame::Close]));
+            assert!(!all_valid(&[Frame::Data(vec![])]));
+            assert!(all_valid(&[Some(3), None]));
+            assert!(!all_valid(&[Some(3), Some(10)]));
+            let value = Some(4u8);
+            assert!(check_matches!(value) && value.check());
+        }
     }
 
     #[test]
-    #[should_panic(expected = "assertion failed: `B(0.5)` does not match `Foo::A(i) if i < 10`")]
-    fn unwrap_match_panics() {
+    fn map_match_res_works() {
+        fn width(field: Option<&str>) -> Result<u8, String> {
+            map_match_res!(field, Some(s) if !s.is_empty() => s.parse::<u8>().map_err(|e| e.to_string()), "missing width".to_string())
+        }
+        assert_eq!(width(Some("12")), Ok(12));
+        assert_eq!(width(Some("300")), Err("number too large to fit in target type".to_string()));
+        assert_eq!(width(Some("")), Err("missing width".to_string()));
+        assert_eq!(width(None), Err("missing width".to_string()));
+    }
+
+    mod assert_matches_approx_struct {
+        struct Point {
+            x: f64,
+            y: f64,
+            label: &'static str,
+        }
+
+        #[test]
+        fn within_tolerance() {
+            let point = Point { x: 1.001, y: -2.0, label: "a" };
+            assert_matches_approx_struct!(point, Point { x: 1.0; 0.01, y: -2.0; 0.0 });
+            assert_matches_approx_struct!(&point, Point { y: -1.5; 0.5 });
+            assert_eq!(point.label, "a");
+        }
+
+        #[test]
+        #[should_panic(expected = "assertion failed: field `y` of `point` is outside the tolerance\n    actual: 2.5\n  expected: 2.0\n tolerance: 0.1")]
+        fn outside_tolerance_panics() {
+            let point = Point { x: 1.0, y: 2.5, label: "b" };
+            assert_matches_approx_struct!(point, Point { x: 1.05; 0.1, y: 2.0; 0.1 });
+        }
+
+        #[test]
+        #[should_panic(expected = "field `x` of `Point { x: f64::NAN, y: 0.0, label: \"c\" }` is outside the tolerance")]
+        fn nan_panics() {
+            assert_matches_approx_struct!(Point { x: f64::NAN, y: 0.0, label: "c" }, Point { x: 0.0; f64::INFINITY });
+        }
+    }
+
+    mod entry_match {
+        use std::collections::{BTreeMap, HashMap};
+
+        #[derive(Clone, Debug, PartialEq)]
+        enum State {
+            Pending(String),
+            Active { since: u64 },
+            Closed,
+        }
+
+        fn states() -> HashMap<u32, State> {
+            let mut states = HashMap::new();
+            states.insert(1, State::Pending("first".into()));
+            states.insert(2, State::Active { since: 10 });
+            states
+        }
+
+        #[test]
+        fn present_and_matching() {
+            let mut states = states();
+            assert!(entry_match!(states, 1, State::Pending(name) if name.starts_with('f') => State::Active { since: name.len() as u64 }));
+            assert_eq!(states[&1], State::Active { since: 5 });
+            assert!(entry_match!(states, 2, State::Active { .. } => State::Closed));
+            assert_eq!(states[&2], State::Closed);
+        }
+
+        #[test]
+        fn present_but_not_matching() {
+            let mut states = states();
+            assert!(!entry_match!(states, 2, State::Pending(_) => State::Closed));
+            assert!(!entry_match!(states, 2, State::Active { since } if *since > 10 => State::Closed));
+            assert_eq!(states, self::states());
+        }
+
+        #[test]
+        fn absent_key() {
+            let mut states = states();
+            assert!(!entry_match!(states, 3, _ => State::Closed));
+            assert_eq!(states, self::states());
+            let mut ordered: BTreeMap<&str, State> = BTreeMap::new();
+            assert!(!entry_match!(ordered, "a", _ => State::Closed));
+            assert!(ordered.is_empty());
+        }
+    }
+
+    mod assert_unique_match {
+        #[derive(Debug, PartialEq)]
+        enum Kind {
+            Small,
+            Even,
+            Large,
+        }
+
+        fn classify(n: u32) -> Kind {
+            assert_unique_match!(n, {
+                Kind::Small => 0..=9 if n % 2 == 1,
+                Kind::Even => n if n % 2 == 0,
+                Kind::Large => 10..,
+            })
+        }
+
+        #[test]
+        fn returns_the_label() {
+            assert_eq!(classify(3), Kind::Small);
+            assert_eq!(classify(4), Kind::Even);
+            let label = assert_unique_match!(Some("x"), { String::from("some") => Some(_), String::from("none") => None });
+            assert_eq!(label, "some");
+        }
+
+        #[test]
+        #[should_panic(expected = "assertion failed: `12` matches more than one labeled pattern: Even, Large")]
+        fn overlapping_patterns_panic() {
+            classify(12);
+        }
+
+        #[test]
+        #[should_panic(expected = "assertion failed: `Some(\"x\")` matches none of the labeled patterns")]
+        fn no_match_panics() {
+            assert_unique_match!(Some("x"), { "none" => None, "empty" => Some("") });
+        }
+    }
+
+    mod guard_temporaries {
+        fn trimmed(s: &str) -> Option<&str> {
+            option_match!(s, x if !x.trim().is_empty() => x.trim())
+        }
+
+        #[test]
+        fn borrowing_results() {
+            assert_eq!(trimmed("  a b "), Some("a b"));
+            assert_eq!(trimmed("   "), None);
+            let owned = String::from(" c ");
+            let borrowed: Option<&str> = option_match!(owned, ref x if !x.trim().is_empty() => x.trim());
+            assert_eq!(borrowed, Some("c"));
+            let lines = [String::from(" d"), String::new()];
+            let nonempty: Vec<&str> = lines.iter().filter_map(|line| option_match!(line, x if !x.trim().is_empty() => x.trim())).collect();
+            assert_eq!(nonempty, ["d"]);
+        }
+
+        #[test]
+        fn owning_results() {
+            let owned = String::from(" e ");
+            let moved: Option<String> = option_match!(owned, x if !x.trim().is_empty() => x.trim().to_string());
+            assert_eq!(moved.as_deref(), Some("e"));
+        }
+    }
+
+    #[test]
+    fn strict_matches_works() {
+        let seen = [1, 5];
+        let x = Some(5);
+        assert!(strict_matches!(x, Some(n) if seen.contains(&n)));
+        assert!(!strict_matches!(x, Some(n) if n > seen[1]));
+        assert!(strict_matches!(x, Some(_)));
+        assert!(strict_matches!(x.map(|n| n * 2), Some(10)));
+        let name = String::from("a");
+        assert!(strict_matches!(name, ref s if s.len() == seen.len() - 1));
+        assert_eq!(name, "a");
+    }
+
+    #[cfg(feature = "std")]
+    mod match_batch {
+        use crate::MatchBatch;
+
+        #[test]
+        fn passes_without_failures() {
+            let mut batch = MatchBatch::new();
+            batch_assert_matches!(&mut batch, Some(1), Some(_));
+            batch_assert_matches!(&mut batch, "ab".len(), 1..=2);
+            assert!(batch.failures().is_empty());
+            batch.finish();
+            MatchBatch::new().finish();
+        }
+
+        #[test]
+        fn reports_every_failure() {
+            let mut batch = MatchBatch::new();
+            batch_assert_matches!(&mut batch, Some(1), Some(_));
+            batch_assert_matches!(&mut batch, Some(2), None);
+            batch_assert_matches!(&mut batch, Ok::<u8, ()>(3), Ok(n) if n > 3);
+            batch_assert_matches!(&mut batch, [1, 2], [_, 2]);
+            assert_eq!(batch.failures().len(), 2);
+            let message = super::panic_message(|| batch.finish());
+            assert_eq!(
+                message,
+                "assertion failed: 2 of 4 batched assertions failed\n  `Some(2)`: `Some(2)` does not match `None`\n  `Ok::<u8, ()>(3)`: `Ok(3)` does not match `Ok(n) if n > 3`"
+            );
+        }
+    }
+
+    mod define_byte_classifier {
+        #[derive(Clone, Copy, Debug, PartialEq)]
+        enum Token {
+            Digit,
+            Hex,
+            Ident,
+            Punct,
+            Control,
+            Other,
+        }
+
+        define_byte_classifier!(
+            /// Classifies a byte of source code.
+            fn classify(byte) -> Token {
+                b'0'..=b'9' => Token::Digit,
+                b'a'..=b'f' | b'A'..=b'F' => Token::Hex,
+                b'g'..=b'z' | b'G'..=b'Z' | b'_' => Token::Ident,
+                b'!'..=b'/' | b':'..=b'@' => Token::Punct,
+                0..=0x1F | 0x7F => Token::Control,
+                _ => Token::Other,
+            }
+        );
+
+        define_byte_classifier!(pub(crate) fn is_vowel(b) -> bool { b'a' | b'e' | b'i' | b'o' | b'u' => true, _ => false });
+
+        const TABLE: [Token; 256] = {
+            let mut table = [Token::Other; 256];
+            let mut byte = 0;
+            while byte < 256 {
+                table[byte] = classify(byte as u8);
+                byte += 1;
+            }
+            table
+        };
+
+        fn reference(byte: u8) -> Token {
+            if byte.is_ascii_digit() {
+                Token::Digit
+            } else if byte.is_ascii_hexdigit() {
+                Token::Hex
+            } else if byte.is_ascii_alphabetic() || byte == b'_' {
+                Token::Ident
+            } else if byte.is_ascii_punctuation() && !matches!(byte, b'[' ..= b'`' | b'{' ..= b'~') {
+                Token::Punct
+            } else if byte.is_ascii_control() {
+                Token::Control
+            } else {
+                Token::Other
+            }
+        }
+
+        #[test]
+        fn matches_brute_force_reference() {
+            for byte in 0..=u8::MAX {
+                let expected = reference(byte);
+                assert_eq!(classify(byte), expected, "byte {:#04x}", byte);
+                assert_eq!(TABLE[byte as usize], expected);
+            }
+        }
+
+        #[test]
+        fn const_and_defaults() {
+            const X: Token = classify(b'x');
+            const VOWEL: bool = is_vowel(b'e');
+            assert_eq!(X, Token::Ident);
+            const _: () = assert!(VOWEL);
+            assert!(!is_vowel(b'y'));
+            assert_eq!(classify(0x80), Token::Other);
+            assert_eq!(classify(b'['), Token::Other);
+        }
+    }
+
+    mod assert_iter_same_variants {
         #[allow(dead_code)]
         #[derive(Debug)]
-        enum Foo {
-            A(u32),
-            B(f32),
+        enum Event {
+            Key(char),
+            Move { x: i32, y: i32 },
+            Quit,
         }
 
-        let foo = Foo::B(0.5);
-        let _i = unwrap_match!(foo, Foo::A(i) if i < 10 => i);
+        fn events() -> Vec<Event> {
+            vec![Event::Key('a'), Event::Move { x: 1, y: 2 }, Event::Quit]
+        }
+
+        #[test]
+        fn same_variants() {
+            let other = [Event::Key('b'), Event::Move { x: 0, y: 0 }, Event::Quit];
+            assert_iter_same_variants!(events(), other.iter());
+            assert_iter_same_variants!(events().iter(), &events());
+            assert_iter_same_variants!(Vec::<Event>::new(), None::<Event>);
+        }
+
+        #[test]
+        #[should_panic(expected = "assertion failed: element 1 differs in variant\n  left: Move { x: 1, y: 2 }\n right: Key('m')")]
+        fn divergence_panics() {
+            assert_iter_same_variants!(events(), vec![Event::Key('k'), Event::Key('m'), Event::Quit]);
+        }
+
+        #[test]
+        #[should_panic(expected = "assertion failed: `events()` has more elements than `events()[..2].iter()`, which ended after 2; next left: Quit")]
+        fn left_longer_panics() {
+            assert_iter_same_variants!(events(), events()[..2].iter());
+        }
+
+        #[test]
+        #[should_panic(expected = "assertion failed: `[Some(1), None]` has more elements than `[Some(2)]`, which ended after 1; next right: None")]
+        fn right_longer_panics() {
+            assert_iter_same_variants!([Some(2)], [Some(1), None]);
+        }
+    }
+
+    mod arm_match {
+        #[derive(Debug)]
+        enum Value {
+            Int(i64),
+            Pair(i64, i64),
+            Text(&'static str),
+        }
+
+        fn total(value: Value) -> i64 {
+            arm_match!(value,
+                Value::Int(n) => n,
+                Value::Pair(a, b) if a < b => b - a,
+                Value::Pair(a, b) => a + b,
+                _ => -1,
+            )
+        }
+
+        #[test]
+        fn arms_and_default() {
+            assert_eq!(total(Value::Int(4)), 4);
+            assert_eq!(total(Value::Pair(1, 5)), 4);
+            assert_eq!(total(Value::Pair(5, 1)), 6);
+            assert_eq!(total(Value::Text("x")), -1);
+            assert_eq!(arm_match!(Some(3), _ if false => 0, Some(n) => n, None => 0), 3);
+            assert_eq!(arm_match!(Value::Text("x"), Value::Text(_) | Value::Int(_) => 1, _ => 2), 1);
+        }
+
+        #[test]
+        fn without_default() {
+            let text = arm_match!(Value::Text("hi"), Value::Text(s) => s.len(), Value::Int(n) => n as usize);
+            assert_eq!(text, 2);
+        }
+
+        #[test]
+        #[should_panic(expected = "assertion failed: `Pair(1, 2)` does not match any of `Value::Int(n)`, `Value::Text(s) if !s.is_empty()`")]
+        fn without_default_panics() {
+            arm_match!(Value::Pair(1, 2), Value::Int(n) => n, Value::Text(s) if !s.is_empty() => s.len() as i64);
+        }
+    }
+
+    mod profile_match {
+        #[derive(Debug)]
+        enum Token {
+            Number(i64),
+            Plus,
+            Minus,
+            Ident(&'static str),
+        }
+
+        fn stream() -> Vec<Token> {
+            vec![Token::Number(1), Token::Plus, Token::Number(-2), Token::Ident("x"), Token::Minus, Token::Number(3)]
+        }
+
+        #[test]
+        fn counts_each_arm() {
+            let mut histogram = vec![0; 4];
+            let mut sum = 0;
+            for token in stream() {
+                sum += profile_match!(histogram, token, {
+                    Token::Number(n) if n > 0 => n,
+                    Token::Number(n) => -n,
+                    Token::Plus | Token::Minus => 0,
+                    Token::Ident(name) => name.len() as i64 * 100,
+                });
+            }
+            assert_eq!(histogram, [2, 1, 2, 1]);
+            assert_eq!(sum, 106);
+
+            let mut counts = [0u64; 2];
+            let slice: &mut [u64] = &mut counts;
+            profile_match!(slice, Some(1), { Some(_) => (), None => () });
+            assert_eq!(counts, [1, 0]);
+        }
+
+        #[test]
+        #[should_panic(expected = "the histogram of `profile_match!` must have a counter for each arm")]
+        fn histogram_length_must_match() {
+            let mut histogram = [0; 3];
+            profile_match!(histogram, true, { true => (), false => () });
+        }
+    }
+
+    mod match_guard_report {
+        use crate::GuardFailure;
+
+        #[derive(Debug)]
+        enum Request {
+            Get { path: &'static str, retries: u32 },
+            Delete,
+        }
+
+        fn report(request: Request) -> Result<(), GuardFailure> {
+            match_guard_report!(request, Request::Get { path, retries }, [
+                path.starts_with('/'),
+                retries < 3,
+                !path.contains(".."),
+            ])
+        }
+
+        #[test]
+        fn reports_first_false_guard() {
+            assert_eq!(report(Request::Get { path: "/index", retries: 0 }), Ok(()));
+            assert_eq!(report(Request::Get { path: "/index", retries: 5 }), Err(GuardFailure::Guard(1)));
+            assert_eq!(report(Request::Get { path: "index", retries: 5 }), Err(GuardFailure::Guard(0)));
+            assert_eq!(report(Request::Get { path: "/../etc", retries: 0 }), Err(GuardFailure::Guard(2)));
+        }
+
+        #[test]
+        fn reports_structural_mismatch() {
+            assert_eq!(report(Request::Delete), Err(GuardFailure::Mismatch));
+            assert_eq!(match_guard_report!(Some(1), Some(1) | Some(2), []), Ok(()));
+            assert_eq!(match_guard_report!(Some(3), Some(1) | Some(2), []), Err(GuardFailure::Mismatch));
+        }
+
+        #[test]
+        fn stops_at_first_false_guard() {
+            let mut evaluated = 0;
+            let mut guard = |result| {
+                evaluated += 1;
+                result
+            };
+            assert_eq!(match_guard_report!(Some(1), Some(_), [guard(true), guard(false), guard(true)]), Err(GuardFailure::Guard(1)));
+            assert_eq!(evaluated, 2);
+        }
+    }
+
+    mod assert_matches_fixpoint {
+        #[derive(Debug)]
+        enum State {
+            Pending(u32),
+            Done(String),
+        }
+
+        fn step(state: State) -> State {
+            match state {
+                State::Pending(0) => State::Done("converged".to_string()),
+                State::Pending(n) => State::Pending(n / 2),
+                done => done,
+            }
+        }
+
+        #[test]
+        fn converges_within_budget() {
+            assert_eq!(assert_matches_fixpoint!(State::Pending(10), step, State::Done(ref s) if s == "converged", 5), 5);
+            assert_eq!(assert_matches_fixpoint!(State::Done(String::new()), step, State::Done(_), 0), 0);
+
+            let mut calls = 0;
+            let iterations = assert_matches_fixpoint!(1.0f64, |x: f64| {
+                calls += 1;
+                (x + 2.0 / x) / 2.0
+            }, x if (x * x - 2.0).abs() < 1e-12, 10);
+            assert_eq!(iterations, calls);
+        }
+
+        #[test]
+        #[should_panic(expected = "assertion failed: `Pending(1)` does not match `State::Done(_)` after 3 iterations")]
+        fn exceeds_iteration_cap() {
+            assert_matches_fixpoint!(State::Pending(10), step, State::Done(_), 3);
+        }
+    }
+
+    mod destructure_match {
+        use core::num::NonZeroU16;
+
+        #[derive(Debug)]
+        struct Config {
+            name: String,
+            port: u16,
+            verbose: bool,
+        }
+
+        #[derive(Debug, PartialEq)]
+        struct ValidatedConfig {
+            name: String,
+            port: NonZeroU16,
+        }
+
+        #[derive(Debug)]
+        enum Source {
+            File { name: String, port: u16 },
+            Missing,
+        }
+
+        fn config() -> Config {
+            Config { name: " server ".to_string(), port: 8080, verbose: true }
+        }
+
+        #[test]
+        fn builds_target_from_fields() {
+            let validated = destructure_match!(config(), Config { name, port, verbose } => ValidatedConfig {
+                name: if verbose { name.trim().to_uppercase() } else { name.trim().to_string() },
+                port: NonZeroU16::new(port).unwrap(),
+            });
+            assert_eq!(validated, ValidatedConfig { name: "SERVER".to_string(), port: NonZeroU16::new(8080).unwrap() });
+
+            let validated = destructure_match!(config(), Config { name, port, .. } => ValidatedConfig {
+                name: name.trim().to_string(),
+                port: NonZeroU16::new(port).unwrap(),
+            });
+            assert_eq!(validated.name, "server");
+        }
+
+        #[test]
+        fn builds_from_enum_variant() {
+            let source = Source::File { name: "local".to_string(), port: 1 };
+            let validated = destructure_match!(source, Source::File { name, port } => ValidatedConfig {
+                name,
+                port: NonZeroU16::new(port).unwrap(),
+            });
+            assert_eq!(validated.port.get(), 1);
+        }
+
+        #[test]
+        #[should_panic(expected = "assertion failed: `Missing` does not match `Source::File { name, .. }`")]
+        fn mismatch_panics() {
+            destructure_match!(Source::Missing, Source::File { name, .. } => ValidatedConfig {
+                name,
+                port: NonZeroU16::new(1).unwrap(),
+            });
+        }
+    }
+
+    mod cfg_assert_matches {
+        #[test]
+        fn active_predicate_asserts() {
+            cfg_assert_matches!(cfg(test), Some(3), Some(1..=5));
+            cfg_assert_matches!(cfg(all(test, feature = "std")), "a".parse::<u8>(), Err(_), "parsing should fail");
+        }
+
+        #[test]
+        #[cfg(not(feature = "claim_compat"))]
+        #[should_panic(expected = "assertion failed: `None` does not match `Some(_)`")]
+        fn active_predicate_panics() {
+            cfg_assert_matches!(cfg(test), None::<u8>, Some(_));
+        }
+
+        #[test]
+        #[should_panic(expected = "expected a value")]
+        fn active_predicate_panics_with_message() {
+            cfg_assert_matches!(cfg(test), None::<u8>, Some(_), "expected a value");
+        }
+
+        #[test]
+        fn inactive_predicate_is_a_no_op() {
+            let evaluated = core::cell::Cell::new(false);
+            cfg_assert_matches!(cfg(not(test)), {
+                evaluated.set(true);
+                None::<u8>
+            }, Some(_));
+            cfg_assert_matches!(cfg(any()), None::<u8>, Some(_), "never checked");
+            assert!(!evaluated.get());
+        }
+    }
+
+    mod span_match {
+        #[derive(Debug, PartialEq)]
+        enum Token {
+            Word(&'static str),
+            Open(char),
+            Close(char),
+        }
+
+        fn tokens() -> Vec<Token> {
+            vec![
+                Token::Word("f"),
+                Token::Open('('),
+                Token::Word("x"),
+                Token::Close(']'),
+                Token::Close(')'),
+                Token::Word("y"),
+            ]
+        }
+
+        #[test]
+        fn extracts_delimited_region() {
+            let tokens = tokens();
+            let (delimiters, span) = span_match!(
+                &tokens,
+                Token::Open(open),
+                Token::Close(close) if (*open, *close) == ('(', ')') => (*open, *close)
+            )
+            .unwrap();
+            assert_eq!(delimiters, ('(', ')'));
+            assert_eq!(span, 1..=4);
+            assert_eq!(tokens[span], [Token::Open('('), Token::Word("x"), Token::Close(']'), Token::Close(')')]);
+
+            let (_, span) = span_match!(&[1, 5, 5, 9][..], 5, 5 => ()).unwrap();
+            assert_eq!(span, 1..=2);
+        }
+
+        #[test]
+        fn missing_end_marker() {
+            let tokens = tokens();
+            assert_eq!(span_match!(&tokens, Token::Open(_), Token::Close('}') => ()), None);
+            assert_eq!(span_match!(&tokens, Token::Word("y"), _ => ()), None);
+            assert_eq!(span_match!(&tokens, Token::Open('['), _ => ()), None);
+        }
+    }
+
+    #[cfg(feature = "either")]
+    mod either_match {
+        use either::Either;
+
+        fn width(value: Either<u8, &str>) -> usize {
+            either_match!(value,
+                Left(n @ 0..=99) => n.to_string().len(),
+                Right(s) if !s.is_empty() => s.len(),
+            )
+        }
+
+        #[test]
+        fn matches_either_side() {
+            assert_eq!(width(Either::Left(7)), 1);
+            assert_eq!(width(Either::Left(42)), 2);
+            assert_eq!(width(Either::Right("abc")), 3);
+        }
+
+        #[test]
+        #[should_panic(expected = "assertion failed: left value `100` does not match `n @ 0..=99`")]
+        fn left_mismatch_names_side() {
+            width(Either::Left(100));
+        }
+
+        #[test]
+        #[should_panic(expected = "assertion failed: right value `\"\"` does not match `s if !s.is_empty()`")]
+        fn right_mismatch_names_side() {
+            width(Either::Right(""));
+        }
+    }
+
+    #[cfg(feature = "std")]
+    mod capture_match {
+        #[derive(Debug)]
+        enum Response {
+            Created { id: u32, name: String },
+            Rejected,
+        }
+
+        define_capture_slot!(static CREATED: (u32, String););
+
+        mod slots {
+            define_capture_slot!(pub(super) static LENGTH: usize;);
+        }
+
+        fn setup(response: Response) {
+            capture_match!(CREATED, response, Response::Created { id, name } => (id, name));
+        }
+
+        fn check() -> Option<(u32, String)> {
+            take_captured!(CREATED)
+        }
+
+        #[test]
+        fn captures_across_functions() {
+            assert_eq!(check(), None);
+            setup(Response::Created { id: 1, name: "a".to_string() });
+            setup(Response::Created { id: 2, name: "b".to_string() });
+            assert_eq!(check(), Some((2, "b".to_string())));
+            assert_eq!(check(), None);
+
+            capture_match!(slots::LENGTH, "abc", s if !s.is_empty() => s.len());
+            assert_eq!(take_captured!(slots::LENGTH), Some(3));
+        }
+
+        #[test]
+        fn slots_are_per_thread() {
+            setup(Response::Created { id: 3, name: "c".to_string() });
+            std::thread::spawn(|| assert_eq!(check(), None)).join().unwrap();
+            assert_eq!(check(), Some((3, "c".to_string())));
+        }
+
+        #[test]
+        #[should_panic(expected = "assertion failed: `Rejected` does not match `Response::Created { id, name }`")]
+        fn mismatch_panics() {
+            setup(Response::Rejected);
+        }
+    }
+
+    #[cfg(feature = "futures")]
+    mod assert_stream_matches {
+        use futures::executor::block_on;
+        use futures::stream::{self, Stream};
+
+        #[derive(Debug)]
+        enum Event {
+            Connected(u16),
+            Data(Vec<u8>),
+            Closed,
+        }
+
+        fn events() -> impl Stream<Item = Event> {
+            stream::iter(vec![Event::Connected(80), Event::Data(vec![1, 2]), Event::Closed])
+        }
+
+        #[test]
+        fn conforming_stream() {
+            block_on(async {
+                assert_stream_matches!(events(), [Event::Connected(80 | 443), Event::Data(ref data) if data.len() == 2, Event::Closed]);
+                assert_stream_matches!(stream::empty::<u8>(), []);
+            });
+        }
+
+        #[test]
+        #[should_panic(expected = "assertion failed: item 1 (`Data([1, 2])`) of the stream does not match `Event::Data(ref data) if data.is_empty()`")]
+        fn divergent_stream() {
+            block_on(async {
+                assert_stream_matches!(events(), [Event::Connected(_), Event::Data(ref data) if data.is_empty(), Event::Closed]);
+            });
+        }
+
+        #[test]
+        #[should_panic(expected = "assertion failed: expected 4 items, but the stream ended after 3")]
+        fn stream_ends_early() {
+            block_on(async {
+                assert_stream_matches!(events(), [Event::Connected(_), Event::Data(_), Event::Closed, Event::Closed]);
+            });
+        }
+
+        #[test]
+        #[should_panic(expected = "assertion failed: expected 2 items, but the stream yielded another one: `Closed`")]
+        fn stream_does_not_end() {
+            block_on(async {
+                assert_stream_matches!(events(), [Event::Connected(_), Event::Data(_)]);
+            });
+        }
+    }
+
+    mod assert_matches_explain {
+        #[derive(Debug)]
+        struct Point {
+            x: i32,
+            y: i32,
+        }
+
+        #[derive(Debug)]
+        enum Shape {
+            Segment(Point, Point),
+            Labeled { name: &'static str, shape: Box<Shape> },
+        }
+
+        fn value(x: i32) -> Option<Result<Point, String>> {
+            Some(Ok(Point { x, y: 0 }))
+        }
+
+        #[test]
+        fn matching_values_pass() {
+            assert_matches_explain!(value(0), Some(Ok(Point { x: 0, .. })));
+            assert_matches_explain!(value(3), Some(Ok(Point { x: 1..=5, y })) if y == 0);
+            assert_matches_explain!((1, "a", Some(2)), (1, "a", Some(_)));
+            assert_matches_explain!(
+                Shape::Segment(Point { x: 0, y: 0 }, Point { x: 1, y: 1 }),
+                Shape::Segment(Point { x: 0, .. }, Point { y: 1, .. })
+            );
+            let segment = Shape::Segment(Point { x: 0, y: 0 }, Point { x: 0, y: 0 });
+            assert_matches_explain!(
+                Shape::Labeled { name: "a", shape: Box::new(segment) },
+                Shape::Labeled { name: "a", ref shape } if matches!(**shape, Shape::Segment(..))
+            );
+        }
+
+        #[test]
+        #[should_panic(expected = "assertion failed: `None` does not match `Some(Ok(Point {")]
+        fn outer_layer() {
+            assert_matches_explain!(None::<Result<Point, String>>, Some(Ok(Point { x: 0, .. })));
+        }
+
+        #[test]
+        #[should_panic(expected = "\n  matched `Some(_)` but field `0` was `Err(\"closed\")`, expected `Ok(Point {")]
+        fn middle_layer() {
+            assert_matches_explain!(Some(Err::<Point, _>("closed".to_string())), Some(Ok(Point { x: 0, .. })));
+        }
+
+        #[test]
+        #[should_panic(expected = "\n  matched `Some(Ok(Point { x: _, .. }))` but field `x` was `5`, expected `0`")]
+        fn innermost_layer() {
+            assert_matches_explain!(value(5), Some(Ok(Point { x: 0, .. })));
+        }
+
+        #[test]
+        #[should_panic(expected = "\n  matched `Shape::Segment(_, Point { y: _, .. })` but field `y` was `2`, expected `1`")]
+        fn later_tuple_field() {
+            assert_matches_explain!(
+                Shape::Segment(Point { x: 0, y: 0 }, Point { x: 1, y: 2 }),
+                Shape::Segment(Point { x: 0, .. }, Point { y: 1, .. })
+            );
+        }
+
+        #[test]
+        #[should_panic(expected = "\n  matched `Shape::Labeled { name: _, .. }` but field `name` was `\"a\"`, expected `\"b\"`")]
+        fn struct_variant_field() {
+            let segment = Shape::Segment(Point { x: 0, y: 0 }, Point { x: 0, y: 0 });
+            assert_matches_explain!(Shape::Labeled { name: "a", shape: Box::new(segment) }, Shape::Labeled { name: "b", .. });
+        }
+
+        #[test]
+        #[should_panic(expected = "\n  matched `(_, _)` but field `1` was `\"b\"`, expected `\"a\"`")]
+        fn tuple_with_literal() {
+            assert_matches_explain!((1, "b"), (1, "a"));
+        }
+
+        #[test]
+        #[should_panic(expected = "` but the guard `y > 0` was false")]
+        fn guard() {
+            assert_matches_explain!(value(0), Some(Ok(Point { x: 0, y })) if y > 0);
+        }
     }
 }
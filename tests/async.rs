@@ -0,0 +1,48 @@
+#[macro_use]
+extern crate matches2;
+
+use futures::executor::block_on;
+
+#[derive(Debug)]
+enum Response {
+    Ok(u16),
+    Redirect(&'static str),
+}
+
+struct Client {
+    status: u16,
+}
+
+impl Client {
+    async fn fetch(&self) -> Result<Response, String> {
+        match self.status {
+            200..=299 => Ok(Response::Ok(self.status)),
+            300..=399 => Ok(Response::Redirect("/elsewhere")),
+            status => Err(format!("status {}", status)),
+        }
+    }
+}
+
+#[test]
+fn macros_accept_await() {
+    block_on(async {
+        let client = Client { status: 200 };
+        assert!(matches!(client.fetch().await, Ok(Response::Ok(200))));
+        assert_matches!(client.fetch().await, Ok(_));
+        assert_eq!(unwrap_match!(client.fetch().await, Ok(Response::Ok(status)) => status), 200);
+        assert_eq!(option_match!(client.fetch().await, Ok(Response::Redirect(to)) => to), None);
+
+        let client = Client { status: 302 };
+        assert_matches!(client.fetch().await, Ok(Response::Redirect(to)) if to.starts_with('/'));
+    });
+}
+
+#[test]
+#[cfg(not(feature = "claim_compat"))]
+#[should_panic(expected = "assertion failed: `Err(\"status 500\")` does not match `Ok(_)`")]
+fn assert_matches_panics_with_await() {
+    block_on(async {
+        let client = Client { status: 500 };
+        assert_matches!(client.fetch().await, Ok(_));
+    });
+}
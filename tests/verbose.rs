@@ -0,0 +1,29 @@
+// Verbose messages read the environment variable, which requires the `std` feature.
+#![cfg(feature = "std")]
+
+#[macro_use]
+extern crate matches2;
+
+use std::env;
+use std::panic;
+
+#[allow(dead_code)]
+#[derive(Debug)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[test]
+fn verbose_panic_message() {
+    env::set_var("MATCHES2_VERBOSE", "1");
+    let result = panic::catch_unwind(|| {
+        assert_matches!(Some(Point { x: 1, y: 2 }), Some(Point { x: 0, .. }));
+    });
+    env::remove_var("MATCHES2_VERBOSE");
+
+    let payload = result.unwrap_err();
+    let message = payload.downcast_ref::<String>().unwrap();
+    assert!(message.contains('\n'), "message is not pretty-printed: {}", message);
+    assert!(message.contains("x: 1,\n"), "message is not pretty-printed: {}", message);
+}